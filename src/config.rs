@@ -1,4 +1,6 @@
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
+
+use crate::graph::{InputFormat, PaletteKind, RunningAvgWindow, SeriesSelector, StepMode};
 
 
 #[derive(Debug)]
@@ -6,7 +8,30 @@ pub enum Error
 {
     ExpectedValue{argument: String},
     ExclusiveArguments{first: String, second: String},
-    NumberParse(String)
+    NumberParse(String),
+    InvalidValue{argument: String, value: String},
+    UnknownPalette(String)
+}
+
+impl fmt::Display for Error
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::ExpectedValue{argument} => write!(f, "{argument} expects a value after it"),
+            Self::ExclusiveArguments{first, second} =>
+            {
+                write!(f, "{first} and {second} cant be used together")
+            },
+            Self::NumberParse(value) => write!(f, "{value:?} isnt a number"),
+            Self::InvalidValue{argument, value} =>
+            {
+                write!(f, "{value:?} isnt a valid value for {argument}")
+            },
+            Self::UnknownPalette(name) => write!(f, "unknown palette {name:?}")
+        }
+    }
 }
 
 pub struct Config
@@ -15,8 +40,41 @@ pub struct Config
     pub min_avg: Option<f64>,
     pub min_height: Option<f64>,
     pub max_height: Option<f64>,
-    pub running_avg: Option<u32>,
+    pub running_avg: Option<RunningAvgWindow>,
     pub plot_line: bool,
+    pub grayscale: bool,
+    pub palette_kind: PaletteKind,
+    pub shadow: bool,
+    pub x_range: Option<(f64, f64)>,
+    pub sdf_lines: bool,
+    pub invert_y: bool,
+    pub guide_alpha: Option<u8>,
+    pub mark_extremes: bool,
+    pub y_labels: Option<usize>,
+    pub mark_plateaus: bool,
+    pub color_by_name: bool,
+    pub fit_range: Option<(f64, f64)>,
+    pub y_precision: Option<usize>,
+    pub marker_stride: Option<usize>,
+    pub y_ticks: Option<Vec<f64>>,
+    pub describe: bool,
+    pub equal_aspect: bool,
+    pub list_colors: bool,
+    pub crosshair: Option<(f64, f64)>,
+    pub percent_difference: Option<(usize, usize)>,
+    pub hide_points: bool,
+    pub input_format: InputFormat,
+    pub grid_on_top: bool,
+    pub last_value: bool,
+    pub strict_palette: bool,
+    pub weight_markers: bool,
+    pub fill_area: bool,
+    pub step: Option<StepMode>,
+    pub smooth: bool,
+    pub select: Option<Vec<SeriesSelector>>,
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    pub label_indices: bool,
     pub paths: Vec<String>
 }
 
@@ -30,6 +88,39 @@ impl Config
         let mut max_height = None;
         let mut running_avg = None;
         let mut plot_line = false;
+        let mut grayscale = false;
+        let mut palette_kind = PaletteKind::default();
+        let mut shadow = false;
+        let mut x_range = None;
+        let mut sdf_lines = false;
+        let mut invert_y = false;
+        let mut guide_alpha = None;
+        let mut mark_extremes = false;
+        let mut y_labels = None;
+        let mut mark_plateaus = false;
+        let mut color_by_name = false;
+        let mut fit_range = None;
+        let mut y_precision = None;
+        let mut marker_stride = None;
+        let mut y_ticks: Option<Vec<f64>> = None;
+        let mut describe = false;
+        let mut equal_aspect = false;
+        let mut list_colors = false;
+        let mut crosshair = None;
+        let mut percent_difference = None;
+        let mut hide_points = false;
+        let mut input_format = InputFormat::default();
+        let mut grid_on_top = false;
+        let mut last_value = false;
+        let mut strict_palette = false;
+        let mut weight_markers = false;
+        let mut fill_area = false;
+        let mut step = None;
+        let mut smooth = false;
+        let mut select: Option<Vec<SeriesSelector>> = None;
+        let mut x_label = None;
+        let mut y_label = None;
+        let mut label_indices = false;
         let mut paths = Vec::new();
 
         while let Some(arg) = args.next()
@@ -54,12 +145,201 @@ impl Config
                 },
                 "-r" | "--running-avg" =>
                 {
-                    running_avg = Some(Self::parse_number(&mut args, arg)?);
+                    let value = args.next().ok_or(Error::ExpectedValue{argument: arg.clone()})?;
+
+                    if value == "auto"
+                    {
+                        running_avg = Some(RunningAvgWindow::Auto);
+
+                        continue;
+                    }
+
+                    let amount: u32 = value.parse().map_err(|_| Error::NumberParse(value))?;
+
+                    if amount == 0
+                    {
+                        return Err(Error::InvalidValue{argument: arg, value: amount.to_string()});
+                    }
+
+                    running_avg = Some(RunningAvgWindow::Fixed(amount));
                 },
                 "-L" | "--line" =>
                 {
                     plot_line = true;
                 },
+                "-g" | "--grayscale" =>
+                {
+                    grayscale = true;
+                },
+                "-p" | "--palette" =>
+                {
+                    let name = args.next().ok_or(Error::ExpectedValue{argument: arg})?;
+
+                    palette_kind = PaletteKind::from_name(&name)
+                        .ok_or(Error::UnknownPalette(name))?;
+                },
+                "-s" | "--shadow" =>
+                {
+                    shadow = true;
+                },
+                "-x" | "--x-range" =>
+                {
+                    let start = Self::parse_number(&mut args, arg.clone())?;
+                    let end = Self::parse_number(&mut args, arg)?;
+
+                    x_range = Some((start, end));
+                },
+                "--sdf" =>
+                {
+                    sdf_lines = true;
+                },
+                "-i" | "--invert" =>
+                {
+                    invert_y = true;
+                },
+                "--guide-alpha" =>
+                {
+                    guide_alpha = Some(Self::parse_number(&mut args, arg)?);
+                },
+                "-e" | "--extremes" =>
+                {
+                    mark_extremes = true;
+                },
+                "--y-labels" =>
+                {
+                    y_labels = Some(Self::parse_number(&mut args, arg)?);
+                },
+                "--plateaus" =>
+                {
+                    mark_plateaus = true;
+                },
+                "-n" | "--name-colors" =>
+                {
+                    color_by_name = true;
+                },
+                "-f" | "--fit-range" =>
+                {
+                    let start = Self::parse_number(&mut args, arg.clone())?;
+                    let end = Self::parse_number(&mut args, arg)?;
+
+                    fit_range = Some((start, end));
+                },
+                "--y-precision" =>
+                {
+                    y_precision = Some(Self::parse_number(&mut args, arg)?);
+                },
+                "--marker-stride" =>
+                {
+                    let stride: usize = Self::parse_number(&mut args, arg.clone())?;
+
+                    if stride == 0
+                    {
+                        return Err(Error::InvalidValue{argument: arg, value: stride.to_string()});
+                    }
+
+                    marker_stride = Some(stride);
+                },
+                "--y-tick" =>
+                {
+                    let tick = Self::parse_number(&mut args, arg)?;
+
+                    y_ticks.get_or_insert_with(Vec::new).push(tick);
+                },
+                "-d" | "--describe" =>
+                {
+                    describe = true;
+                },
+                "-a" | "--equal-aspect" =>
+                {
+                    equal_aspect = true;
+                },
+                "--list-colors" =>
+                {
+                    list_colors = true;
+                },
+                "-c" | "--crosshair" =>
+                {
+                    let x = Self::parse_number(&mut args, arg.clone())?;
+                    let y = Self::parse_number(&mut args, arg)?;
+
+                    crosshair = Some((x, y));
+                },
+                "--diff" =>
+                {
+                    let a = Self::parse_number(&mut args, arg.clone())?;
+                    let b = Self::parse_number(&mut args, arg)?;
+
+                    percent_difference = Some((a, b));
+                },
+                "--no-points" =>
+                {
+                    hide_points = true;
+                },
+                "--categories" =>
+                {
+                    input_format = InputFormat::Categories;
+                },
+                "--format" =>
+                {
+                    let name = args.next().ok_or(Error::ExpectedValue{argument: arg.clone()})?;
+
+                    input_format = InputFormat::from_name(&name)
+                        .ok_or(Error::InvalidValue{argument: arg, value: name})?;
+                },
+                "--grid-on-top" =>
+                {
+                    grid_on_top = true;
+                },
+                "--last-value" =>
+                {
+                    last_value = true;
+                },
+                "--strict-palette" =>
+                {
+                    strict_palette = true;
+                },
+                "--weight-markers" =>
+                {
+                    weight_markers = true;
+                },
+                "--fill" =>
+                {
+                    fill_area = true;
+                },
+                "--step" =>
+                {
+                    let name = args.next().ok_or(Error::ExpectedValue{argument: arg.clone()})?;
+
+                    step = Some(StepMode::from_name(&name)
+                        .ok_or(Error::InvalidValue{argument: arg, value: name})?);
+                },
+                "--smooth" =>
+                {
+                    smooth = true;
+                },
+                "--select" =>
+                {
+                    let value = args.next().ok_or(Error::ExpectedValue{argument: arg})?;
+
+                    // numbers pick by index, anything else by name
+                    let selector = value.parse()
+                        .map(SeriesSelector::Index)
+                        .unwrap_or(SeriesSelector::Name(value));
+
+                    select.get_or_insert_with(Vec::new).push(selector);
+                },
+                "--x-label" =>
+                {
+                    x_label = Some(args.next().ok_or(Error::ExpectedValue{argument: arg})?);
+                },
+                "--y-label" =>
+                {
+                    y_label = Some(args.next().ok_or(Error::ExpectedValue{argument: arg})?);
+                },
+                "--indices" =>
+                {
+                    label_indices = true;
+                },
                 _ =>
                 {
                     paths.push(arg);
@@ -82,6 +362,39 @@ impl Config
             max_height,
             running_avg,
             plot_line,
+            grayscale,
+            palette_kind,
+            shadow,
+            x_range,
+            sdf_lines,
+            invert_y,
+            guide_alpha,
+            mark_extremes,
+            y_labels,
+            mark_plateaus,
+            color_by_name,
+            fit_range,
+            y_precision,
+            marker_stride,
+            y_ticks,
+            describe,
+            equal_aspect,
+            list_colors,
+            crosshair,
+            percent_difference,
+            hide_points,
+            input_format,
+            grid_on_top,
+            last_value,
+            strict_palette,
+            weight_markers,
+            fill_area,
+            step,
+            smooth,
+            select,
+            x_label,
+            y_label,
+            label_indices,
             paths
         })
     }
@@ -91,8 +404,35 @@ impl Config
         arg: String
     ) -> Result<T, Error>
     {
-        let value = args.next().ok_or_else(|| Error::ExpectedValue{argument: arg})?;
+        let value = args.next().ok_or(Error::ExpectedValue{argument: arg})?;
         
         value.parse().map_err(|_| Error::NumberParse(value))
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn parse(args: &[&str]) -> Result<Config, Error>
+    {
+        Config::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn input_format()
+    {
+        let config = parse(&["--format", "csv", "data.txt"]).unwrap();
+
+        assert_eq!(config.input_format, InputFormat::Categories);
+        assert_eq!(config.paths, ["data.txt"]);
+
+        assert_eq!(parse(&["data.txt"]).unwrap().input_format, InputFormat::Plain);
+        assert_eq!(parse(&["--categories"]).unwrap().input_format, InputFormat::Categories);
+
+        assert!(matches!(parse(&["--format", "json"]), Err(Error::InvalidValue{..})));
+        assert!(matches!(parse(&["--format"]), Err(Error::ExpectedValue{..})));
+    }
+}
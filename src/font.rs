@@ -1,4 +1,12 @@
-use std::collections::HashMap;
+use std::{
+    fmt,
+    fs,
+    error::Error,
+    path::Path,
+    str::FromStr,
+    collections::HashMap,
+    sync::{Arc, Mutex}
+};
 
 use crate::Point2;
 
@@ -37,11 +45,78 @@ impl FontChar
     {
         self.width + self.step
     }
+
+    // how far the glyph reaches below 0, like the tail on a g
+    pub fn descent(&self) -> f64
+    {
+        let lowest = self.lines.iter().flat_map(|line| [line.start.y, line.end.y]).fold(0.0, f64::min);
+
+        -lowest
+    }
+}
+
+#[derive(Debug)]
+pub enum FontParseError
+{
+    // line numbers start at 1
+    UnknownDirective{line: usize, value: String},
+    InvalidChar{line: usize, value: String},
+    InvalidNumber{line: usize, value: String},
+    WrongCoordinateCount{line: usize, amount: usize},
+    OutsideGlyph{line: usize},
+    MissingField{line: usize, field: &'static str}
+}
+
+impl fmt::Display for FontParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::UnknownDirective{line, value} =>
+            {
+                write!(f, "line {line} starts with an unknown directive: {value:?}")
+            },
+            Self::InvalidChar{line, value} =>
+            {
+                write!(f, "line {line} should name a single character or U+hex, got: {value:?}")
+            },
+            Self::InvalidNumber{line, value} =>
+            {
+                write!(f, "line {line} has an invalid number: {value:?}")
+            },
+            Self::WrongCoordinateCount{line, amount} =>
+            {
+                write!(f, "line {line} should have 4 coordinates (start x y, end x y), got {amount}")
+            },
+            Self::OutsideGlyph{line} =>
+            {
+                write!(f, "line {line} describes a glyph before any char or fallback line")
+            },
+            Self::MissingField{line, field} =>
+            {
+                write!(f, "glyph starting at line {line} is missing its {field}")
+            }
+        }
+    }
 }
 
+impl Error for FontParseError {}
+
+// character, image size, then the glyph size and thickness snapped to a grid
+pub(crate) type GlyphKey = (char, Point2<usize>, Point2<u32>, u32);
+
+// every image size and text size gets its own glyphs, so the cache has to stop somewhere
+const GLYPH_CACHE_LIMIT: usize = 4096;
+
 pub struct Font
 {
-    chars: HashMap<char, FontChar>
+    chars: HashMap<char, FontChar>,
+    fallback: FontChar,
+    use_fallback: bool,
+    line_height: f64,
+    // rasterized glyphs as pixel offsets from their bottom left corner
+    cache: Mutex<HashMap<GlyphKey, Arc<[Point2<i32>]>>>
 }
 
 impl Default for Font
@@ -212,6 +287,46 @@ impl Default for Font
                 width: 0.1,
                 step: default_step
             }),
+            ('-', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.5}, Point2{x: 1.0, y: 0.5})
+                    .build(),
+                width: 0.5,
+                step: default_step
+            }),
+            ('+', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.5}, Point2{x: 1.0, y: 0.5})
+                    .teleport(Point2{x: 0.5, y: 0.2}, Point2{x: 0.5, y: 0.8})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            (',', FontChar{
+                lines: Builder::begin(Point2{x: 1.0, y: 0.05}, Point2{x: 0.0, y: -0.2})
+                    .build(),
+                width: 0.15,
+                step: default_step
+            }),
+            (':', FontChar{
+                lines: Builder::begin(Point2{x: 0.4, y: 0.0}, Point2{x: 0.4, y: 0.0})
+                    .teleport(Point2{x: 0.4, y: 0.6}, Point2{x: 0.4, y: 0.6})
+                    .build(),
+                width: 0.1,
+                step: default_step
+            }),
+            ('/', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.0}, Point2{x: 1.0, y: 1.0})
+                    .build(),
+                width: 0.5,
+                step: default_step
+            }),
+            ('%', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.0}, Point2{x: 1.0, y: 1.0})
+                    .teleport(Point2{x: 0.1, y: 0.85}, Point2{x: 0.1, y: 0.85})
+                    .teleport(Point2{x: 0.9, y: 0.15}, Point2{x: 0.9, y: 0.15})
+                    .build(),
+                width: 0.7,
+                step: default_step
+            }),
             ('A', FontChar{
                 lines: Builder::begin(Point2{x: 0.0, y: 0.0}, Point2{x: 0.5, y: 1.0})
                     .move_to(Point2{x: 1.0, y: 0.0})
@@ -437,6 +552,225 @@ impl Default for Font
                 width: 0.7,
                 step: default_step
             }),
+            ('a', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.6}, Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 0.0, y: 0.3})
+                    .move_to(Point2{x: 1.0, y: 0.3})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('b', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 1.0}, Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 0.0, y: 0.6})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('c', FontChar{
+                lines: Builder::begin(Point2{x: 1.0, y: 0.6}, Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .build(),
+                width: 0.5,
+                step: default_step
+            }),
+            ('d', FontChar{
+                lines: Builder::begin(Point2{x: 1.0, y: 1.0}, Point2{x: 1.0, y: 0.0})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('e', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.3}, Point2{x: 1.0, y: 0.3})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('f', FontChar{
+                lines: Builder::begin(Point2{x: 1.0, y: 1.0}, Point2{x: 0.3, y: 1.0})
+                    .move_to(Point2{x: 0.3, y: 0.0})
+                    .teleport(Point2{x: 0.0, y: 0.6}, Point2{x: 0.9, y: 0.6})
+                    .build(),
+                width: 0.5,
+                step: default_step
+            }),
+            ('g', FontChar{
+                lines: Builder::begin(Point2{x: 1.0, y: 0.0}, Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: -0.3})
+                    .move_to(Point2{x: 0.0, y: -0.3})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('h', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 1.0}, Point2{x: 0.0, y: 0.0})
+                    .teleport(Point2{x: 0.0, y: 0.6}, Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('i', FontChar{
+                lines: Builder::begin(Point2{x: 0.5, y: 0.0}, Point2{x: 0.5, y: 0.6})
+                    .teleport(Point2{x: 0.5, y: 0.85}, Point2{x: 0.5, y: 0.85})
+                    .build(),
+                width: 0.1,
+                step: default_step
+            }),
+            ('j', FontChar{
+                lines: Builder::begin(Point2{x: 1.0, y: 0.6}, Point2{x: 1.0, y: -0.3})
+                    .move_to(Point2{x: 0.0, y: -0.3})
+                    .teleport(Point2{x: 1.0, y: 0.85}, Point2{x: 1.0, y: 0.85})
+                    .build(),
+                width: 0.3,
+                step: default_step
+            }),
+            ('k', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 1.0}, Point2{x: 0.0, y: 0.0})
+                    .teleport(Point2{x: 1.0, y: 0.6}, Point2{x: 0.0, y: 0.25})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('l', FontChar{
+                lines: Builder::begin(Point2{x: 0.5, y: 1.0}, Point2{x: 0.5, y: 0.0})
+                    .build(),
+                width: 0.1,
+                step: default_step
+            }),
+            ('m', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.0}, Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .teleport(Point2{x: 0.5, y: 0.6}, Point2{x: 0.5, y: 0.0})
+                    .build(),
+                width: 0.8,
+                step: default_step
+            }),
+            ('n', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.0}, Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('o', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.6}, Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .move_to_index(0)
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('p', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: -0.3}, Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('q', FontChar{
+                lines: Builder::begin(Point2{x: 1.0, y: -0.3}, Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('r', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.0}, Point2{x: 0.0, y: 0.6})
+                    .teleport(Point2{x: 0.0, y: 0.4}, Point2{x: 0.4, y: 0.6})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .build(),
+                width: 0.5,
+                step: default_step
+            }),
+            ('s', FontChar{
+                lines: Builder::begin(Point2{x: 1.0, y: 0.6}, Point2{x: 0.0, y: 0.6})
+                    .move_to(Point2{x: 0.0, y: 0.3})
+                    .move_to(Point2{x: 1.0, y: 0.3})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .build(),
+                width: 0.5,
+                step: default_step
+            }),
+            ('t', FontChar{
+                lines: Builder::begin(Point2{x: 0.3, y: 1.0}, Point2{x: 0.3, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .teleport(Point2{x: 0.0, y: 0.6}, Point2{x: 1.0, y: 0.6})
+                    .build(),
+                width: 0.5,
+                step: default_step
+            }),
+            ('u', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.6}, Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('v', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.6}, Point2{x: 0.5, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .build(),
+                width: 0.5,
+                step: default_step
+            }),
+            ('w', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.6}, Point2{x: 0.2, y: 0.0})
+                    .move_to(Point2{x: 0.5, y: 0.4})
+                    .move_to(Point2{x: 0.8, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.6})
+                    .build(),
+                width: 0.8,
+                step: default_step
+            }),
+            ('x', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.6}, Point2{x: 1.0, y: 0.0})
+                    .teleport(Point2{x: 0.0, y: 0.0}, Point2{x: 1.0, y: 0.6})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('y', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.6}, Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .teleport(Point2{x: 1.0, y: 0.6}, Point2{x: 1.0, y: -0.3})
+                    .move_to(Point2{x: 0.0, y: -0.3})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
+            ('z', FontChar{
+                lines: Builder::begin(Point2{x: 0.0, y: 0.6}, Point2{x: 1.0, y: 0.6})
+                    .move_to(Point2{x: 0.0, y: 0.0})
+                    .move_to(Point2{x: 1.0, y: 0.0})
+                    .build(),
+                width: 0.6,
+                step: default_step
+            }),
             (' ', FontChar{
                 lines: Vec::new(),
                 width: 0.5,
@@ -444,14 +778,414 @@ impl Default for Font
             })
         ].into_iter().collect();
 
-        Self{chars}
+        // a box for characters the font doesnt have
+        let fallback = FontChar{
+            lines: Builder::begin(Point2{x: 0.0, y: 0.0}, Point2{x: 1.0, y: 0.0})
+                .move_to(Point2{x: 1.0, y: 1.0})
+                .move_to(Point2{x: 0.0, y: 1.0})
+                .move_to_index(0)
+                .build(),
+            width: 0.6,
+            step: default_step
+        };
+
+        // every glyph is drawn between y 0 and 1, lowercase letters are 0.6 tall
+        // and only their descenders dip below to -0.3, text boxes grow to fit those
+        Self{chars, fallback, use_fallback: false, line_height: 1.0, cache: Mutex::default()}
+    }
+}
+
+// a glyph thats still being read, target is none for the fallback
+struct PendingGlyph
+{
+    target: Option<char>,
+    line: usize,
+    width: Option<f64>,
+    step: Option<f64>,
+    lines: Vec<Line>
+}
+
+impl PendingGlyph
+{
+    fn new(target: Option<char>, line: usize) -> Self
+    {
+        Self{target, line, width: None, step: None, lines: Vec::new()}
+    }
+
+    fn finish(self) -> Result<(Option<char>, FontChar), FontParseError>
+    {
+        let missing = |field| FontParseError::MissingField{line: self.line, field};
+
+        let width = self.width.ok_or_else(|| missing("width"))?;
+        let step = self.step.ok_or_else(|| missing("step"))?;
+
+        Ok((self.target, FontChar{lines: self.lines, width, step}))
+    }
+}
+
+// blocks that start with `char X` (or `char U+hex`) or `fallback`, then `width`, `step`
+// and a `start_x start_y end_x end_y` row for every line, `line_height` can go before
+// the first block and lines starting with # are comments
+impl FromStr for Font
+{
+    type Err = FontParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err>
+    {
+        let mut chars = HashMap::new();
+        let mut fallback = None;
+        let mut line_height = 1.0;
+
+        let mut pending: Option<PendingGlyph> = None;
+
+        let mut finish = |pending: Option<PendingGlyph>| -> Result<(), FontParseError>
+        {
+            if let Some(pending) = pending
+            {
+                match pending.finish()?
+                {
+                    (Some(c), glyph) => { chars.insert(c, glyph); },
+                    (None, glyph) => fallback = Some(glyph)
+                }
+            }
+
+            Ok(())
+        };
+
+        for (index, line) in s.lines().enumerate()
+        {
+            let line_number = index + 1;
+
+            // not trimmed so a space can be its own character
+            if let Some(value) = line.strip_prefix("char ")
+            {
+                let target = Self::parse_char(value)
+                    .ok_or_else(|| FontParseError::InvalidChar{line: line_number, value: value.to_owned()})?;
+
+                finish(pending.replace(PendingGlyph::new(Some(target), line_number)))?;
+
+                continue;
+            }
+
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#')
+            {
+                continue;
+            }
+
+            let mut tokens = line.split_whitespace();
+            let directive = tokens.next().expect("line isnt empty");
+
+            let parse = |value: &str|
+            {
+                value.parse::<f64>().map_err(|_|
+                {
+                    FontParseError::InvalidNumber{line: line_number, value: value.to_owned()}
+                })
+            };
+
+            let single = |value: Option<&str>| parse(value.unwrap_or(""));
+
+            if directive == "fallback"
+            {
+                finish(pending.replace(PendingGlyph::new(None, line_number)))?;
+
+                continue;
+            }
+
+            if directive == "line_height" && pending.is_none()
+            {
+                line_height = single(tokens.next())?;
+
+                continue;
+            }
+
+            let glyph = pending.as_mut().ok_or(FontParseError::OutsideGlyph{line: line_number})?;
+
+            match directive
+            {
+                "width" => glyph.width = Some(single(tokens.next())?),
+                "step" => glyph.step = Some(single(tokens.next())?),
+                _ =>
+                {
+                    if directive.parse::<f64>().is_err()
+                    {
+                        return Err(FontParseError::UnknownDirective{
+                            line: line_number,
+                            value: directive.to_owned()
+                        });
+                    }
+
+                    let values = line.split_whitespace().map(parse).collect::<Result<Vec<_>, _>>()?;
+
+                    let [start_x, start_y, end_x, end_y] = values[..]
+                    else
+                    {
+                        return Err(FontParseError::WrongCoordinateCount{
+                            line: line_number,
+                            amount: values.len()
+                        });
+                    };
+
+                    glyph.lines.push(Line{
+                        start: Point2{x: start_x, y: start_y},
+                        end: Point2{x: end_x, y: end_y}
+                    });
+                }
+            }
+        }
+
+        finish(pending)?;
+
+        // same box as the default font if the file doesnt have its own
+        let fallback = fallback.unwrap_or_else(|| Self::default().fallback);
+
+        Ok(Self{chars, fallback, use_fallback: false, line_height, cache: Mutex::default()})
+    }
+}
+
+// writes the same format from_str reads, characters in order so the output is stable
+impl fmt::Display for Font
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        let write_glyph = |f: &mut fmt::Formatter, glyph: &FontChar|
+        {
+            writeln!(f, "width {}", glyph.width)?;
+            writeln!(f, "step {}", glyph.step)?;
+
+            glyph.lines.iter().try_for_each(|Line{start, end}|
+            {
+                writeln!(f, "{} {} {} {}", start.x, start.y, end.x, end.y)
+            })
+        };
+
+        writeln!(f, "line_height {}", self.line_height)?;
+
+        let mut chars = self.chars.iter().collect::<Vec<_>>();
+        chars.sort_by_key(|(c, _)| **c);
+
+        for (c, glyph) in chars
+        {
+            if c.is_whitespace()
+            {
+                writeln!(f, "\nchar U+{:04X}", *c as u32)?;
+            } else
+            {
+                writeln!(f, "\nchar {c}")?;
+            }
+
+            write_glyph(f, glyph)?;
+        }
+
+        writeln!(f, "\nfallback")?;
+        write_glyph(f, &self.fallback)
     }
 }
 
 impl Font
 {
+    #[allow(dead_code)]
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>>
+    {
+        Ok(fs::read_to_string(path)?.parse()?)
+    }
+
+    fn parse_char(value: &str) -> Option<char>
+    {
+        if let Some(hex) = value.strip_prefix("U+")
+        {
+            return u32::from_str_radix(hex, 16).ok().and_then(char::from_u32);
+        }
+
+        let mut chars = value.chars();
+
+        match (chars.next(), chars.next())
+        {
+            (Some(c), None) => Some(c),
+            _ => None
+        }
+    }
+
     pub fn get(&self, c: char) -> Option<&FontChar>
     {
         self.chars.get(&c)
     }
+
+    // unknown characters get skipped unless the fallback is enabled
+    pub fn get_or_fallback(&self, c: char) -> Option<&FontChar>
+    {
+        self.get(c).or(self.use_fallback.then_some(&self.fallback))
+    }
+
+    pub fn line_height(&self, size: Point2<f64>) -> f64
+    {
+        self.line_height * size.y
+    }
+
+    pub(crate) fn cached_glyph(
+        &self,
+        key: GlyphKey,
+        rasterize: impl FnOnce() -> Vec<Point2<i32>>
+    ) -> Arc<[Point2<i32>]>
+    {
+        // a panic while holding the lock cant leave a half written glyph behind
+        let mut cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+
+        // rasterizing is deterministic so starting over only costs time
+        if cache.len() >= GLYPH_CACHE_LIMIT && !cache.contains_key(&key)
+        {
+            cache.clear();
+        }
+
+        cache.entry(key).or_insert_with(|| rasterize().into()).clone()
+    }
+
+    #[allow(dead_code)]
+    pub fn with_fallback(mut self, state: bool) -> Self
+    {
+        self.use_fallback = state;
+
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn set_fallback(&mut self, state: bool)
+    {
+        self.use_fallback = state;
+    }
+
+    #[allow(dead_code)]
+    pub fn contains(&self, c: char) -> bool
+    {
+        self.chars.contains_key(&c)
+    }
+
+    #[allow(dead_code)]
+    pub fn supported(&self) -> impl Iterator<Item=char> + '_
+    {
+        self.chars.keys().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn same_glyph(a: &FontChar, b: &FontChar) -> bool
+    {
+        a.width == b.width && a.step == b.step && a.lines.len() == b.lines.len()
+            && a.lines.iter().zip(b.lines.iter()).all(|(a, b)| a.start == b.start && a.end == b.end)
+    }
+
+    #[test]
+    fn default_font_round_trips()
+    {
+        let font = Font::default();
+        let text = font.to_string();
+
+        let parsed: Font = text.parse().unwrap();
+
+        assert_eq!(parsed.to_string(), text);
+        assert_eq!(parsed.line_height, font.line_height);
+        assert!(same_glyph(&parsed.fallback, &font.fallback));
+
+        assert_eq!(parsed.chars.len(), font.chars.len());
+        font.chars.iter().for_each(|(c, glyph)|
+        {
+            let other = parsed.get(*c).unwrap_or_else(|| panic!("{c:?} went missing"));
+
+            assert!(same_glyph(glyph, other), "{c:?} changed");
+        });
+    }
+
+    #[test]
+    fn parses_a_small_font()
+    {
+        let text = "line_height 1.5\n\n# a comment\nchar U+0020\nwidth 0.2\nstep 0.1\n\nchar /\nwidth 0.5\nstep 0.1\n0 0 0.5 1\n";
+        let font: Font = text.parse().unwrap();
+
+        assert_eq!(font.line_height, 1.5);
+        assert!(font.get(' ').unwrap().lines().is_empty());
+
+        let slash = font.get('/').unwrap();
+        assert_eq!(slash.width(), 0.5);
+        assert_eq!(slash.lines()[0].end, Point2{x: 0.5, y: 1.0});
+    }
+
+    #[test]
+    fn malformed_coordinate()
+    {
+        let err = "char a\nwidth 0.5\nstep 0.3\n0 0 1 x".parse::<Font>().err().unwrap();
+
+        assert!(
+            matches!(&err, FontParseError::InvalidNumber{line: 4, value} if value == "x"),
+            "got {err:?}"
+        );
+    }
+
+    #[test]
+    fn wrong_coordinate_count()
+    {
+        let err = "char a\nwidth 0.5\nstep 0.3\n0 0 1".parse::<Font>().err().unwrap();
+
+        assert!(matches!(err, FontParseError::WrongCoordinateCount{line: 4, amount: 3}), "got {err:?}");
+
+        let err = "char a\nwidth 0.5\nstep 0.3\n0 0 1 1 0".parse::<Font>().err().unwrap();
+
+        assert!(matches!(err, FontParseError::WrongCoordinateCount{line: 4, amount: 5}), "got {err:?}");
+    }
+
+    #[test]
+    fn other_errors()
+    {
+        let err = "width 0.5".parse::<Font>().err().unwrap();
+        assert!(matches!(err, FontParseError::OutsideGlyph{line: 1}), "got {err:?}");
+
+        let err = "char ab".parse::<Font>().err().unwrap();
+        assert!(matches!(err, FontParseError::InvalidChar{line: 1, ..}), "got {err:?}");
+
+        let err = "char a\nwdith 0.5".parse::<Font>().err().unwrap();
+        assert!(matches!(err, FontParseError::UnknownDirective{line: 2, ..}), "got {err:?}");
+
+        let err = "char a\nwidth 0.5\nstep".parse::<Font>().err().unwrap();
+        assert!(matches!(err, FontParseError::InvalidNumber{line: 3, ..}), "got {err:?}");
+
+        let err = "char a\nwidth 0.5\n\nchar b\nwidth 0.5\nstep 0.1".parse::<Font>().err().unwrap();
+        assert!(matches!(err, FontParseError::MissingField{line: 1, field: "step"}), "got {err:?}");
+    }
+
+
+    #[test]
+    fn has_lowercase()
+    {
+        let font = Font::default();
+
+        ('a'..='z').for_each(|c| assert!(font.get(c).is_some(), "{c:?} is missing"));
+
+        assert_eq!(font.get('a').unwrap().descent(), 0.0);
+        assert!((font.get('g').unwrap().descent() - 0.3).abs() < 1e-9);
+    }
+
+
+    #[test]
+    fn has_punctuation()
+    {
+        let font = Font::default();
+
+        ['-', '+', ',', ':', '/', '%'].into_iter().for_each(|c|
+        {
+            let glyph = font.get(c).unwrap_or_else(|| panic!("{c:?} is missing"));
+
+            assert!(!glyph.lines().is_empty(), "{c:?} has nothing to draw");
+        });
+
+        let lines = |text: &str| text.chars().filter_map(|c| font.get(c)).map(|c| c.lines().len()).sum::<usize>();
+
+        assert!(lines("-5") > lines("5"));
+        assert!(lines("5%") > lines("5"));
+    }
 }
@@ -1,65 +1,93 @@
 use std::{
     f64,
+    fmt,
+    mem,
+    iter,
     error::Error,
     io::{self, BufReader, BufRead},
     fs::File,
-    path::Path
+    path::Path,
+    ops::Index
 };
 
 use crate::{
+    stats,
     PPMImage,
     Font,
     Color,
     ColorRepr,
     ColorAlpha,
     BoundingBox,
+    LineStyle,
     TextHAlign,
     TextVAlign,
+    clip_line,
+    ImageFormat,
     Point2
 };
 
+pub use crate::stats::{Line, PearsonCorrCoeff, Prediction};
+
 
 #[derive(Debug, Clone, Copy)]
 pub struct PointType
 {
     pub color: Option<Color>,
-    pub pos: Point2<f64>
+    pub pos: Point2<f64>,
+    // 1.0 unless the data has a weight column, biases the fit lines and can size the markers
+    pub weight: f64
 }
 
-struct RunningAverage
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunningAvgWindow
 {
-    amount: u32,
-    values: Vec<f64>
+    Fixed(u32),
+    // picked from the amount of points with Grapher::auto_running_avg
+    Auto
 }
 
-impl RunningAverage
+impl RunningAvgWindow
 {
-    pub fn new(amount: u32) -> Self
+    pub fn amount(&self, points: usize) -> u32
     {
-        Self{
-            amount,
-            values: Vec::new()
+        match self
+        {
+            Self::Fixed(amount) => *amount,
+            Self::Auto => Grapher::auto_running_avg(points)
         }
     }
+}
 
-    #[allow(dead_code)]
-    pub fn is_empty(&self) -> bool
-    {
-        self.values.is_empty()
-    }
+struct RunningAverage
+{
+    values: Vec<f64>
+}
 
-    pub fn push(&mut self, points: &[PointType])
+impl RunningAverage
+{
+    pub fn new(amount: u32, points: &Points) -> Self
     {
-        let take_amount = points.len().min(self.amount as usize);
+        // a window of 0 would divide by zero
+        let amount = amount.max(1);
 
-        let s: f64 = points.iter().map(|point|
+        let averages = points.rolling_apply(amount as usize, |values|
         {
-            point.pos.y
-        }).rev().take(take_amount).sum();
+            values.iter().rev().sum::<f64>() / values.len() as f64
+        });
+
+        // each average is over the points before it, so the first one has nothing to average
+        let values = iter::once(f64::NAN)
+            .chain(averages.iter().map(|point| point.pos.y))
+            .take(points.len())
+            .collect();
 
-        let value = s / take_amount as f64;
+        Self{values}
+    }
 
-        self.values.push(value);
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool
+    {
+        self.values.is_empty()
     }
 
     pub fn values(&self) -> &[f64]
@@ -68,33 +96,28 @@ impl RunningAverage
     }
 }
 
-pub struct Line
-{
-    pub slope: f64,
-    pub intercept: f64
-}
-
-impl Line
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct SeriesSummary
 {
-    pub fn at_x(&self, x: f64) -> f64
-    {
-        self.slope * x + self.intercept
-    }
-
-    pub fn at_y(&self, y: f64) -> f64
-    {
-        (y - self.intercept) / self.slope
-    }
+    pub name: Option<String>,
+    pub count: usize,
+    pub lowest: Option<f64>,
+    pub highest: Option<f64>,
+    pub mean: Option<f64>,
+    // needs at least 3 points
+    pub correlation: Option<PearsonCorrCoeff>
 }
 
-#[derive(Debug, Clone, Copy)]
-pub struct PearsonCorrCoeff
+#[allow(dead_code)]
+#[derive(Debug, Clone)]
+pub struct GraphSummary
 {
-    pub r: f64,
-    pub p: f64
+    pub series: Vec<SeriesSummary>,
+    pub bounds: BoundingBox
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct Points(pub Vec<PointType>);
 
 #[allow(dead_code)]
@@ -105,206 +128,209 @@ impl Points
         Self(Vec::new())
     }
 
-    pub fn map<F: FnMut(PointType) -> PointType>(mut self, mut map: F) -> Points
+    pub fn len(&self) -> usize
     {
-        self.0.iter_mut().for_each(|p| *p = map(*p));
-
-        self
+        self.0.len()
     }
 
-    fn mean_of(values: impl Iterator<Item=f64>, len: usize) -> f64
+    pub fn is_empty(&self) -> bool
     {
-        values.sum::<f64>() / len as f64
+        self.0.is_empty()
     }
 
-    fn mean(values: impl ExactSizeIterator<Item=f64>) -> f64
+    pub fn iter(&self) -> impl ExactSizeIterator<Item=&PointType> + Clone
     {
-        let len = values.len();
-
-        Self::mean_of(values, len)
+        self.0.iter()
     }
 
-    fn sample_standard_deviation(values: impl ExactSizeIterator<Item=f64> + Clone) -> f64
+    pub fn map<F: FnMut(PointType) -> PointType>(mut self, mut map: F) -> Points
     {
-        let mean = Self::mean(values.clone());
-
-        let len = values.len();
-        let variance = Self::mean_of(values.map(|x| (x - mean).powi(2)), len - 1);
+        self.0.iter_mut().for_each(|p| *p = map(*p));
 
-        variance.sqrt()
+        self
     }
 
-    fn standard_scores(
-        values: impl ExactSizeIterator<Item=f64> + Clone
-    ) -> impl ExactSizeIterator<Item=f64> + Clone
+    fn positions(&self) -> impl ExactSizeIterator<Item=Point2<f64>> + Clone + '_
     {
-        let mean = Self::mean(values.clone());
-        let standard_deviation = Self::sample_standard_deviation(values.clone());
-
-        values.map(move |x| (x - mean) / standard_deviation)
+        self.0.iter().map(|p| p.pos)
     }
 
     pub fn mean_x(&self) -> f64
     {
-        Self::mean(self.0.iter().map(|p| p.pos.x))
+        stats::mean(self.0.iter().map(|p| p.pos.x))
     }
 
     pub fn mean_y(&self) -> f64
     {
-        Self::mean(self.0.iter().map(|p| p.pos.y))
+        stats::mean(self.0.iter().map(|p| p.pos.y))
     }
 
     pub fn pearson_corr_coeff(&self) -> PearsonCorrCoeff
     {
-        let len = self.0.len();
+        stats::pearson_corr_coeff(self.positions())
+    }
 
-        let standard_scores_x = Self::standard_scores(self.0.iter().map(|p| p.pos.x));
-        let standard_scores_y = Self::standard_scores(self.0.iter().map(|p| p.pos.y));
+    // linear interpolation between the neighbours, points have to be sorted by x
+    pub fn interpolate(&self, x: f64) -> Option<f64>
+    {
+        let after = self.0.partition_point(|point| point.pos.x < x);
 
-        let r = Self::mean_of(
-            standard_scores_x.zip(standard_scores_y).map(|(x, y)| x * y),
-            len - 1
-        );
+        let next = self.0.get(after)?;
+        if next.pos.x == x
+        {
+            return Some(next.pos.y);
+        }
 
-        let df = (len - 2) as f64;
+        let previous = self.0.get(after.checked_sub(1)?)?;
 
-        let t = (r / (1.0 - r.powi(2)).sqrt()) * df.sqrt();
+        let t = (x - previous.pos.x) / (next.pos.x - previous.pos.x);
 
-        let p = Self::students_t(df, t);
+        Some(previous.pos.y + (next.pos.y - previous.pos.y) * t)
+    }
+
+    // (self - other) / other in percent at each x of self, other gets interpolated
+    // points outside of other or where other is zero are left out
+    pub fn percent_difference(&self, other: &Points) -> Points
+    {
+        Points(self.0.iter().filter_map(|point|
+        {
+            let b = other.interpolate(point.pos.x)?;
 
-        PearsonCorrCoeff{r, p}
+            (b != 0.0).then(||
+            {
+                let y = (point.pos.y - b) / b * 100.0;
+
+                PointType{color: None, pos: Point2{y, ..point.pos}, weight: point.weight}
+            })
+        }).collect())
     }
 
-    // approximating the beta function was a mistake lmao
+    // needs at least 3 points with different x values
     #[allow(dead_code)]
-    fn integrate_exclude_start<F>(start: f64, end: f64, steps: u32, f: F) -> f64
-    where
-        F: Fn(f64) -> f64
+    pub fn predict(&self, x: f64) -> Option<Prediction>
     {
-        assert!(steps > 2);
+        stats::predict(self.positions(), x)
+    }
 
-        let steps = steps * 2;
+    // f gets the y values of up to window points ending at each point
+    pub fn rolling_apply(&self, window: usize, f: impl Fn(&[f64]) -> f64) -> Points
+    {
+        let window = window.max(1);
 
-        let area = end - start;
+        let values = self.0.iter().map(|point| point.pos.y).collect::<Vec<_>>();
 
-        let step = (steps as f64).recip();
+        Points(self.0.iter().enumerate().map(|(index, point)|
+        {
+            let start = (index + 1).saturating_sub(window);
 
-        // uses simpson's rule
+            PointType{pos: Point2{y: f(&values[start..=index]), ..point.pos}, ..*point}
+        }).collect())
+    }
 
-        let steps = steps + 1;
-        let value_at = |n|
+    // sigma is in points, so this expects evenly spaced x values
+    #[allow(dead_code)]
+    pub fn smooth_gaussian(&self, sigma: f64) -> Points
+    {
+        if sigma.is_nan() || sigma <= 0.0
         {
-            let n = n as f64 / steps as f64;
+            return self.clone();
+        }
 
-            start + n * area
-        };
+        // anything past 3 sigma barely contributes
+        let radius = (sigma * 3.0).ceil() as usize;
 
-        let s: f64 = (1..=steps).map(|n|
+        let kernel = (0..=radius).map(|distance|
         {
-            let value = f(value_at(n));
+            (-((distance * distance) as f64) / (2.0 * sigma * sigma)).exp()
+        }).collect::<Vec<_>>();
 
-            let n = n - 1;
-            let factor = if (n == 0) || (n == steps - 1)
-            {
-                1.0
-            } else if n % 2 == 1
-            {
-                4.0
-            } else
+        let len = self.0.len();
+        Points((0..len).map(|index|
+        {
+            let start = index.saturating_sub(radius);
+            let end = (index + radius).min(len.saturating_sub(1));
+
+            // normalize by the used weights so the edges dont sag
+            let (sum, total_weight) = (start..=end).fold((0.0, 0.0), |(sum, total), other|
             {
-                2.0
-            };
+                let weight = kernel[index.abs_diff(other)];
 
-            let result = value * factor;
+                (sum + self.0[other].pos.y * weight, total + weight)
+            });
 
-            result * step
-        }).sum();
+            let point = self.0[index];
 
-        s / 3.0
+            PointType{pos: Point2{y: sum / total_weight, ..point.pos}, ..point}
+        }).collect())
     }
 
-    fn gamma_big(z: f64) -> f64
+    // runs of consecutive points with the same y, as inclusive index ranges
+    pub fn plateaus(&self) -> Vec<(usize, usize)>
     {
-        let terms = 1.0 + [
-            (1.0, 12.0),
-            (1.0, 288.0),
-            (-139.0, 51840.0),
-            (-571.0, 2488320.0),
-            (163879.0, 209018880.0),
-            (524819.0, 75246796800.0),
-            (-534703531.0, 902961561600.0)
-        ].into_iter().enumerate().map(|(index, (top, bottom))|
-        {
-            let p = index + 1;
+        let mut runs = Vec::new();
 
-            top / (bottom * z.powi(p as i32))
-        }).sum::<f64>();
+        let mut start = 0;
+        for index in 1..=self.0.len()
+        {
+            let same = self.0.get(index).map(|point|
+            {
+                point.pos.y == self.0[start].pos.y
+            }).unwrap_or(false);
 
-        (2.0 * f64::consts::PI / z).sqrt() * (z / f64::consts::E).powf(z) * terms
-    }
+            if !same
+            {
+                if index - start > 1
+                {
+                    runs.push((start, index - 1));
+                }
 
-    fn gamma(z: f64) -> f64
-    {
-        // i think this is accurate enough
-        if z < 20.0
-        {
-            Self::gamma(z + 1.0) / z
-        } else
-        {
-            Self::gamma_big(z)
+                start = index;
+            }
         }
+
+        runs
     }
 
-    // not using this, its implemented with gamma anyway
-    #[allow(dead_code)]
-    fn beta(x: f64, y: f64) -> f64
+    // does it with least squares difference
+    pub fn best_fit_line(&self) -> Line
     {
-        /*Self::integrate_exclude_start(0.0, 1.0, 25000, |t|
-        {
-            let left = t.powf(x - 1.0);
-            let right = (1.0 - t).powf(y - 1.0);
-
-            left * right
-        })*/
-        
-        let top = Self::gamma(x) * Self::gamma(y);
-        let bottom = Self::gamma(x + y);
-
-        top / bottom
+        stats::best_fit_line(self.positions())
     }
 
-    fn students_t(df: f64, t: f64) -> f64
+    // same as best_fit_line but every point counts as much as its weight
+    pub fn best_fit_line_weighted(&self) -> Line
     {
-        let vh = (df + 1.0) / 2.0;
-
-        let top = Self::gamma(vh);
-        let bottom = (f64::consts::PI * df).sqrt() * Self::gamma(df / 2.0);
-
-        let right = (1.0 + (t.powi(2) / df)).powf(vh).recip();
-
-        (top / bottom) * right
+        stats::best_fit_line_weighted(self.0.iter().map(|p| (p.pos, p.weight)))
     }
 
-    // does it with least squares difference
-    pub fn best_fit_line(&self) -> Line
+    // same as best_fit_line_weighted but only with the points inside the x window
+    pub fn best_fit_line_range(&self, x0: f64, x1: f64) -> Option<Line>
     {
-        let mean_x = self.mean_x();
-        let mean_y = self.mean_y();
+        let (start, end) = (x0.min(x1), x0.max(x1));
 
-        let top: f64 = self.0.iter().map(|p|
+        let window = Points(self.0.iter().filter(|point|
         {
-            (p.pos.x - mean_x) * (p.pos.y - mean_y)
-        }).sum();
+            (start..=end).contains(&point.pos.x)
+        }).copied().collect());
 
-        let bottom: f64 = self.0.iter().map(|p|
+        if window.len() < 2
         {
-            (p.pos.x - mean_x).powi(2)
-        }).sum();
+            return None;
+        }
 
-        let slope = top / bottom;
-        let intercept = mean_y - slope * mean_x;
+        let line = window.best_fit_line_weighted();
 
-        Line{slope, intercept}
+        line.slope.is_finite().then_some(line)
+    }
+}
+
+impl Index<usize> for Points
+{
+    type Output = PointType;
+
+    fn index(&self, index: usize) -> &Self::Output
+    {
+        &self.0[index]
     }
 }
 
@@ -312,7 +338,6 @@ pub struct Graph(GraphBuilder);
 
 impl Graph
 {
-    #[allow(dead_code)]
     pub fn highest(&self) -> Option<f64>
     {
         self.0.highest_point
@@ -333,6 +358,11 @@ impl Graph
         &self.0.points
     }
 
+    pub fn name(&self) -> Option<&str>
+    {
+        self.0.name.as_deref()
+    }
+
     pub fn points_slice(&self) -> &[PointType]
     {
         &self.0.points.0
@@ -358,26 +388,56 @@ impl Graph
 
 pub struct GraphBuilder
 {
+    name: Option<String>,
     points: Points,
+    running_avg_window: Option<RunningAvgWindow>,
     running_avg: Option<RunningAverage>,
+    x_range: Option<(f64, f64)>,
     lowest_point: Option<f64>,
     highest_point: Option<f64>
 }
 
+#[allow(dead_code)]
 impl GraphBuilder
 {
-    pub fn new(running_avg: Option<u32>) -> Self
+    pub fn new(running_avg: Option<RunningAvgWindow>) -> Self
     {
         Self{
+            name: None,
             points: Points::new(),
-            running_avg: running_avg.map(|amount| RunningAverage::new(amount)),
+            running_avg_window: running_avg,
+            running_avg: None,
+            x_range: None,
             lowest_point: None,
             highest_point: None
         }
     }
 
+    pub fn with_name(mut self, name: impl Into<String>) -> Self
+    {
+        self.name = Some(name.into());
+
+        self
+    }
+
+    // points pushed after this with x outside of start..=end get dropped
+    pub fn with_x_range(mut self, x_range: Option<(f64, f64)>) -> Self
+    {
+        self.x_range = x_range;
+
+        self
+    }
+
     pub fn push(&mut self, p: PointType)
     {
+        if let Some((start, end)) = self.x_range
+        {
+            if !(start..=end).contains(&p.pos.x)
+            {
+                return;
+            }
+        }
+
         self.points.0.push(p);
 
         let Point2{x: _x, y} = p.pos;
@@ -405,6 +465,27 @@ impl GraphBuilder
         }).unwrap_or(y));
     }
 
+    // running extremes of the y values pushed so far
+    pub fn lowest(&self) -> Option<f64>
+    {
+        self.lowest_point
+    }
+
+    pub fn highest(&self) -> Option<f64>
+    {
+        self.highest_point
+    }
+
+    pub fn len(&self) -> usize
+    {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool
+    {
+        self.points.is_empty()
+    }
+
     pub fn complete(mut self) -> Graph
     {
         self.points.0.sort_unstable_by(|a, b|
@@ -412,607 +493,2907 @@ impl GraphBuilder
             a.pos.x.partial_cmp(&b.pos.x).expect("values must be comparable")
         });
 
-        if let Some(running_avg) = self.running_avg.as_mut()
+        if let Some(window) = self.running_avg_window
         {
-            (0..self.points.0.len()).for_each(|x| running_avg.push(&self.points.0[0..x]));
+            let amount = window.amount(self.points.len());
+
+            self.running_avg = Some(RunningAverage::new(amount, &self.points));
         }
 
         Graph(self)
     }
 }
 
-pub struct GrapherConfig
+// how much of the image height goes to an axis title and the gap around it
+const AXIS_TITLE_BAND: f64 = 0.06;
+const AXIS_TITLE_MARGIN: f64 = 0.01;
+
+// the room an axis title takes up, nothing if theres no title
+fn title_band(label: &Option<String>) -> f64
 {
-    pub log_scale: Option<f64>,
-    pub min_avg: Option<f64>,
-    pub min_height: Option<f64>,
-    pub max_height: Option<f64>,
-    pub running_avg: Option<u32>,
-    pub plot_line: bool,
-    pub font: Font
+    if label.is_some() { AXIS_TITLE_BAND } else { 0.0 }
 }
 
-impl Default for GrapherConfig
+// room on the right for the percent difference labels
+const DIFFERENCE_AXIS_BAND: f64 = 0.15;
+
+// past this many points in a series the index labels would just be noise
+const INDEX_LABEL_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SeriesSelector
 {
-    fn default() -> Self
+    Index(usize),
+    Name(String)
+}
+
+impl SeriesSelector
+{
+    pub fn matches(&self, index: usize, graph: &Graph) -> bool
     {
-        Self{
-            log_scale: None,
-            min_avg: None,
-            min_height: None,
-            max_height: None,
-            running_avg: None,
-            plot_line: false,
-            font: Font::default()
+        match self
+        {
+            Self::Index(selected) => *selected == index,
+            Self::Name(name) => graph.name() == Some(name.as_str())
         }
     }
 }
 
-type Padding = BoundingBox;
-
-#[allow(dead_code)]
-pub struct Grapher
+// which way the line goes first when going to the next point
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepMode
 {
-    top: f64,
-    bottom: f64,
-    left: f64,
-    right: f64,
-    config: GrapherConfig,
-    graphs: Vec<Graph>
+    HorizontalFirst,
+    VerticalFirst
 }
 
-impl Grapher
+impl StepMode
 {
-    pub fn new(config: GrapherConfig) -> Self
+    pub fn from_name(name: &str) -> Option<Self>
     {
-        Self{
-            top: 0.0,
-            bottom: f64::MAX,
-            left: 0.0,
-            right: 0.0,
-            config,
-            graphs: Vec::new()
+        match name
+        {
+            "horizontal" | "h" => Some(Self::HorizontalFirst),
+            "vertical" | "v" => Some(Self::VerticalFirst),
+            _ => None
         }
     }
+}
 
-    #[allow(dead_code)]
-    pub fn from_graphs(graphs: Vec<Graph>, config: GrapherConfig) -> Self
-    {
-        let mut this = Self::new(config);
+// how the lines of a data file get read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputFormat
+{
+    // a value per line with an optional weight, and step lines
+    #[default]
+    Plain,
+    // comma separated x, category, y and an optional weight
+    Categories
+}
 
-        graphs.iter().for_each(|graph|
+impl InputFormat
+{
+    pub fn from_name(name: &str) -> Option<Self>
+    {
+        match name
         {
-            this.fit_graph(graph);
-        });
+            "plain" => Some(Self::Plain),
+            "categories" | "csv" => Some(Self::Categories),
+            _ => None
+        }
+    }
+}
 
-        this.graphs = graphs;
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaletteKind
+{
+    #[default]
+    Default,
+    OkabeIto
+}
 
-        this
+impl PaletteKind
+{
+    pub fn all() -> impl Iterator<Item=Self>
+    {
+        [Self::Default, Self::OkabeIto].into_iter()
     }
 
-    pub fn parse(&mut self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>>
+    pub fn name(&self) -> &'static str
     {
-        let f = File::open(path)?;
-        let reader = BufReader::new(f);
-
-        let mut x_step = 1.0;
-        let mut x = 0.0;
-
-        let mut this_graph = GraphBuilder::new(self.config.running_avg);
-
-        for line in reader.lines()
+        match self
         {
-            let line = line?;
-
-            if line.starts_with("step")
-            {
-                let step = &line["step".len()..];
-                let step: f64 = step.trim().parse()?;
-
-                x_step = step;
-
-                continue;
-            }
-
-            let value: f64 = line.trim().parse()?;
-
-            x += x_step;
-            this_graph.push(PointType{color: None, pos: Point2{x, y: value}});
+            Self::Default => "default",
+            Self::OkabeIto => "okabe-ito"
         }
-
-        let this_graph = this_graph.complete();
-
-        self.fit_graph(&this_graph);
-        self.graphs.push(this_graph);
-
-        Ok(())
     }
 
-    fn fit_graph(&mut self, graph: &Graph)
+    pub fn from_name(name: &str) -> Option<Self>
     {
-        if let Some(last) = graph.last()
+        match name
         {
-            self.right = self.right.max(last.pos.x);
+            "default" => Some(Self::Default),
+            "okabe-ito" | "colorblind" => Some(Self::OkabeIto),
+            _ => None
         }
+    }
 
-        let mut lowest = f64::MAX;
-
-        let mut average = 0.0;
-        graph.points_slice().iter().for_each(|point|
-        {
-            if let Some(max_height) = self.config.max_height
-            {
-                self.top = max_height;
-            } else
-            {
-                self.top = self.top.max(point.pos.y);
-            }
-
-            if self.config.min_avg.is_some()
-            {
-                average += point.pos.y;
-            }
-
-            lowest = lowest.min(point.pos.y);
-        });
-
-        if let Some(min_height) = self.config.min_height
-        {
-            self.bottom = min_height;
-        } else if self.bottom > lowest
+    pub fn colors(&self) -> Vec<Color>
+    {
+        match self
         {
-            self.bottom = if let Some(scale) = self.config.min_avg
-            {
-                let average = average / graph.points_slice().len() as f64;
-
-                let diff = (average - lowest).abs();
-
-                lowest - (diff * scale)
-            } else
-            {
-                lowest
-            };
+            Self::Default => vec![
+                Color{r: 255, g: 120, b: 120},
+                Color{r: 120, g: 255, b: 120},
+                Color{r: 120, g: 120, b: 255},
+                Color{r: 255, g: 120, b: 220},
+                Color{r: 255, g: 220, b: 120}
+            ],
+            // colorblind safe, black is left out cuz it blends with the borders
+            Self::OkabeIto => vec![
+                Color{r: 230, g: 159, b: 0},
+                Color{r: 86, g: 180, b: 233},
+                Color{r: 0, g: 158, b: 115},
+                Color{r: 240, g: 228, b: 66},
+                Color{r: 0, g: 114, b: 178},
+                Color{r: 213, g: 94, b: 0},
+                Color{r: 204, g: 121, b: 167}
+            ]
         }
     }
+}
 
-    pub fn save(&self, size: Point2<usize>, path: impl AsRef<Path>) -> io::Result<()>
+// palette colors first, after that hues spread out by the golden ratio
+pub fn series_color(index: usize, config: &GrapherConfig) -> Color
+{
+    let palette = config.palette_kind.colors();
+
+    if let Some(color) = palette.get(index)
     {
-        self.to_image(size).save(path)
+        return *color;
     }
 
-    pub fn to_image(&self, size: Point2<usize>) -> PPMImage
+    let index = index - palette.len();
+
+    let golden_ratio = 0.618_034;
+    let hue = (index as f64 * golden_ratio).fract() * 360.0;
+
+    // cycle the brightness too so close hues dont end up as the same color
+    let lap = (index / 3) % 4;
+    let value = 0.95 - lap as f32 * 0.1;
+
+    Color::from_hsv(hue as f32, 0.6, value)
+}
+
+pub struct GrapherConfig
+{
+    pub log_scale: Option<f64>,
+    pub min_avg: Option<f64>,
+    pub min_height: Option<f64>,
+    pub max_height: Option<f64>,
+    pub running_avg: Option<RunningAvgWindow>,
+    pub plot_line: bool,
+    pub grayscale: bool,
+    pub palette_kind: PaletteKind,
+    pub shadow: bool,
+    pub x_range: Option<(f64, f64)>,
+    pub sdf_lines: bool,
+    pub invert_y: bool,
+    pub guide_alpha: u8,
+    pub mark_extremes: bool,
+    pub y_labels: usize,
+    // indexed by parse order, series with higher values get drawn on top
+    pub z_order: Vec<i32>,
+    pub mark_plateaus: bool,
+    pub color_by_name: bool,
+    pub fit_range: Option<(f64, f64)>,
+    pub y_precision: usize,
+    // only every nth point gets a circle, the line still goes through all of them
+    pub marker_stride: usize,
+    pub y_ticks: Option<Vec<f64>>,
+    pub equal_aspect: bool,
+    pub crosshair: Option<Point2<f64>>,
+    pub show_points: bool,
+    pub grid_on_top: bool,
+    pub last_value: bool,
+    pub strict_palette: bool,
+    // theres no separate size column, the weight column is what sizes the markers
+    pub weight_markers: bool,
+    pub fill_area: bool,
+    pub step: Option<StepMode>,
+    pub smooth: bool,
+    // only these series get drawn, the bounds still come from all of them
+    pub select: Option<Vec<SeriesSelector>>,
+    pub x_label: Option<String>,
+    pub y_label: Option<String>,
+    pub label_indices: bool,
+    // (a - b) / b in percent between the series at these indices, with its own axis on the right
+    pub percent_difference: Option<(usize, usize)>,
+    pub font: Font
+}
+
+impl Default for GrapherConfig
+{
+    fn default() -> Self
+    {
+        Self{
+            log_scale: None,
+            min_avg: None,
+            min_height: None,
+            max_height: None,
+            running_avg: None,
+            plot_line: false,
+            grayscale: false,
+            palette_kind: PaletteKind::default(),
+            shadow: false,
+            x_range: None,
+            sdf_lines: false,
+            invert_y: false,
+            guide_alpha: 15,
+            mark_extremes: false,
+            y_labels: 3,
+            z_order: Vec::new(),
+            mark_plateaus: false,
+            color_by_name: false,
+            fit_range: None,
+            y_precision: 4,
+            marker_stride: 1,
+            y_ticks: None,
+            equal_aspect: false,
+            crosshair: None,
+            show_points: true,
+            grid_on_top: false,
+            last_value: false,
+            strict_palette: false,
+            weight_markers: false,
+            fill_area: false,
+            step: None,
+            smooth: false,
+            select: None,
+            x_label: None,
+            y_label: None,
+            label_indices: false,
+            percent_difference: None,
+            font: Font::default()
+        }
+    }
+}
+
+impl GrapherConfig
+{
+    #[allow(dead_code)]
+    pub fn builder() -> GrapherConfigBuilder
+    {
+        GrapherConfigBuilder::new()
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError>
+    {
+        if self.min_avg.is_some() && self.min_height.is_some()
+        {
+            return Err(ConfigError::ExclusiveOptions{
+                first: "min_avg",
+                second: "min_height"
+            });
+        }
+
+        if self.running_avg == Some(RunningAvgWindow::Fixed(0))
+        {
+            return Err(ConfigError::ZeroRunningAvg);
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError
+{
+    ExclusiveOptions{first: &'static str, second: &'static str},
+    ZeroRunningAvg,
+    PaletteTooSmall{series: usize, colors: usize}
+}
+
+impl fmt::Display for ConfigError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::ExclusiveOptions{first, second} =>
+            {
+                write!(f, "{first} and {second} cant be used together")
+            },
+            Self::ZeroRunningAvg => write!(f, "running average window must be at least 1"),
+            Self::PaletteTooSmall{series, colors} =>
+            {
+                write!(f, "{series} series dont fit in a palette with {colors} colors")
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[derive(Debug)]
+pub enum ParseError
+{
+    // line numbers start at 1
+    MissingColumns{line: usize},
+    ExtraColumns{line: usize},
+    InvalidNumber{line: usize, column: &'static str, value: String}
+}
+
+impl fmt::Display for ParseError
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            Self::MissingColumns{line} =>
+            {
+                write!(f, "line {line} should look like: x, category, y")
+            },
+            Self::ExtraColumns{line} =>
+            {
+                write!(f, "line {line} has more columns after the weight")
+            },
+            Self::InvalidNumber{line, column, value} =>
+            {
+                write!(f, "line {line} has an invalid {column} value: {value:?}")
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Default)]
+pub struct GrapherConfigBuilder(GrapherConfig);
+
+#[allow(dead_code)]
+impl GrapherConfigBuilder
+{
+    pub fn new() -> Self
+    {
+        Self::default()
+    }
+
+    pub fn log_scale(mut self, scale: f64) -> Self
+    {
+        self.0.log_scale = Some(scale);
+
+        self
+    }
+
+    pub fn min_avg(mut self, scale: f64) -> Self
+    {
+        self.0.min_avg = Some(scale);
+
+        self
+    }
+
+    pub fn min_height(mut self, height: f64) -> Self
+    {
+        self.0.min_height = Some(height);
+
+        self
+    }
+
+    pub fn max_height(mut self, height: f64) -> Self
+    {
+        self.0.max_height = Some(height);
+
+        self
+    }
+
+    pub fn running_avg(mut self, amount: u32) -> Self
+    {
+        self.0.running_avg = Some(RunningAvgWindow::Fixed(amount));
+
+        self
+    }
+
+    pub fn running_avg_auto(mut self) -> Self
+    {
+        self.0.running_avg = Some(RunningAvgWindow::Auto);
+
+        self
+    }
+
+    pub fn plot_line(mut self, state: bool) -> Self
+    {
+        self.0.plot_line = state;
+
+        self
+    }
+
+    pub fn grayscale(mut self, state: bool) -> Self
+    {
+        self.0.grayscale = state;
+
+        self
+    }
+
+    pub fn palette_kind(mut self, palette_kind: PaletteKind) -> Self
+    {
+        self.0.palette_kind = palette_kind;
+
+        self
+    }
+
+    pub fn shadow(mut self, state: bool) -> Self
+    {
+        self.0.shadow = state;
+
+        self
+    }
+
+    pub fn x_range(mut self, start: f64, end: f64) -> Self
+    {
+        self.0.x_range = Some((start, end));
+
+        self
+    }
+
+    pub fn sdf_lines(mut self, state: bool) -> Self
+    {
+        self.0.sdf_lines = state;
+
+        self
+    }
+
+    pub fn invert_y(mut self, state: bool) -> Self
+    {
+        self.0.invert_y = state;
+
+        self
+    }
+
+    pub fn guide_alpha(mut self, alpha: u8) -> Self
+    {
+        self.0.guide_alpha = alpha;
+
+        self
+    }
+
+    pub fn mark_extremes(mut self, state: bool) -> Self
+    {
+        self.0.mark_extremes = state;
+
+        self
+    }
+
+    pub fn y_labels(mut self, amount: usize) -> Self
+    {
+        self.0.y_labels = amount;
+
+        self
+    }
+
+    pub fn z_order(mut self, z_order: Vec<i32>) -> Self
+    {
+        self.0.z_order = z_order;
+
+        self
+    }
+
+    pub fn mark_plateaus(mut self, state: bool) -> Self
+    {
+        self.0.mark_plateaus = state;
+
+        self
+    }
+
+    pub fn color_by_name(mut self, state: bool) -> Self
+    {
+        self.0.color_by_name = state;
+
+        self
+    }
+
+    pub fn fit_range(mut self, start: f64, end: f64) -> Self
+    {
+        self.0.fit_range = Some((start, end));
+
+        self
+    }
+
+    pub fn y_precision(mut self, digits: usize) -> Self
+    {
+        self.0.y_precision = digits;
+
+        self
+    }
+
+    pub fn marker_stride(mut self, stride: usize) -> Self
+    {
+        self.0.marker_stride = stride;
+
+        self
+    }
+
+    pub fn y_ticks(mut self, ticks: Vec<f64>) -> Self
+    {
+        self.0.y_ticks = Some(ticks);
+
+        self
+    }
+
+    pub fn equal_aspect(mut self, state: bool) -> Self
+    {
+        self.0.equal_aspect = state;
+
+        self
+    }
+
+    pub fn crosshair(mut self, at: Point2<f64>) -> Self
+    {
+        self.0.crosshair = Some(at);
+
+        self
+    }
+
+    pub fn show_points(mut self, state: bool) -> Self
+    {
+        self.0.show_points = state;
+
+        self
+    }
+
+    pub fn grid_on_top(mut self, state: bool) -> Self
+    {
+        self.0.grid_on_top = state;
+
+        self
+    }
+
+    pub fn last_value(mut self, state: bool) -> Self
+    {
+        self.0.last_value = state;
+
+        self
+    }
+
+    pub fn strict_palette(mut self, state: bool) -> Self
+    {
+        self.0.strict_palette = state;
+
+        self
+    }
+
+    pub fn weight_markers(mut self, state: bool) -> Self
+    {
+        self.0.weight_markers = state;
+
+        self
+    }
+
+    pub fn fill_area(mut self, state: bool) -> Self
+    {
+        self.0.fill_area = state;
+
+        self
+    }
+
+    pub fn step(mut self, step: StepMode) -> Self
+    {
+        self.0.step = Some(step);
+
+        self
+    }
+
+    pub fn smooth(mut self, state: bool) -> Self
+    {
+        self.0.smooth = state;
+
+        self
+    }
+
+    pub fn select(mut self, select: Vec<SeriesSelector>) -> Self
+    {
+        self.0.select = Some(select);
+
+        self
+    }
+
+    pub fn x_label(mut self, label: impl Into<String>) -> Self
+    {
+        self.0.x_label = Some(label.into());
+
+        self
+    }
+
+    pub fn y_label(mut self, label: impl Into<String>) -> Self
+    {
+        self.0.y_label = Some(label.into());
+
+        self
+    }
+
+    pub fn label_indices(mut self, state: bool) -> Self
+    {
+        self.0.label_indices = state;
+
+        self
+    }
+
+    pub fn percent_difference(mut self, a: usize, b: usize) -> Self
+    {
+        self.0.percent_difference = Some((a, b));
+
+        self
+    }
+
+    pub fn font(mut self, font: Font) -> Self
+    {
+        self.0.font = font;
+
+        self
+    }
+
+    pub fn build(self) -> Result<GrapherConfig, ConfigError>
+    {
+        self.0.validate()?;
+
+        Ok(self.0)
+    }
+}
+
+type Padding = BoundingBox;
+
+#[allow(dead_code)]
+pub struct Grapher
+{
+    top: f64,
+    bottom: f64,
+    left: f64,
+    right: f64,
+    config: GrapherConfig,
+    graphs: Vec<Graph>
+}
+
+impl Grapher
+{
+    // about 50 windows over the whole graph
+    pub fn auto_running_avg(points: usize) -> u32
+    {
+        (points / 50).max(1) as u32
+    }
+
+    pub fn new(config: GrapherConfig) -> Result<Self, ConfigError>
+    {
+        config.validate()?;
+
+        let left = config.x_range.map(|(start, _)| start).unwrap_or(0.0);
+
+        Ok(Self{
+            top: 0.0,
+            bottom: f64::MAX,
+            left,
+            right: left,
+            config,
+            graphs: Vec::new()
+        })
+    }
+
+    #[allow(dead_code)]
+    pub fn from_graphs(graphs: Vec<Graph>, config: GrapherConfig) -> Result<Self, ConfigError>
+    {
+        let mut this = Self::new(config)?;
+
+        this.graphs = graphs;
+        this.recompute_bounds();
+
+        Ok(this)
+    }
+
+    pub fn parse_format(&mut self, path: impl AsRef<Path>, format: InputFormat) -> Result<(), Box<dyn Error>>
+    {
+        match format
+        {
+            InputFormat::Plain => self.parse(path),
+            InputFormat::Categories => self.parse_categories(path)
+        }
+    }
+
+    fn graph_builder(&self) -> GraphBuilder
+    {
+        GraphBuilder::new(self.config.running_avg).with_x_range(self.config.x_range)
+    }
+
+    pub fn parse(&mut self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>>
+    {
+        let path = path.as_ref();
+
+        let f = File::open(path)?;
+        let reader = BufReader::new(f);
+
+        let mut x_step = 1.0;
+        let mut x = 0.0;
+
+        let mut this_graph = self.graph_builder();
+
+        if let Some(name) = path.file_stem()
+        {
+            this_graph = this_graph.with_name(name.to_string_lossy());
+        }
+
+        for (index, line) in reader.lines().enumerate()
+        {
+            let line = line?;
+
+            if let Some(step) = line.strip_prefix("step")
+            {
+                let step: f64 = step.trim().parse()?;
+
+                x_step = step;
+
+                continue;
+            }
+
+            // value and an optional weight after it
+            let mut columns = line.split_whitespace();
+
+            let value: f64 = columns.next().unwrap_or_default().parse()?;
+            let weight: f64 = columns.next().map(|weight| weight.parse()).transpose()?.unwrap_or(1.0);
+
+            if columns.next().is_some()
+            {
+                return Err(ParseError::ExtraColumns{line: index + 1}.into());
+            }
+
+            x += x_step;
+            this_graph.push(PointType{color: None, pos: Point2{x, y: value}, weight});
+        }
+
+        self.graphs.push(this_graph.complete());
+        self.recompute_bounds();
+
+        Ok(())
+    }
+
+    // rows of x, category, y where every category turns into its own graph
+    pub fn parse_categories(&mut self, path: impl AsRef<Path>) -> Result<(), Box<dyn Error>>
+    {
+        let f = File::open(path)?;
+        let reader = BufReader::new(f);
+
+        let mut graphs: Vec<(String, GraphBuilder)> = Vec::new();
+
+        for (index, line) in reader.lines().enumerate()
+        {
+            let line = line?;
+            let line_number = index + 1;
+
+            if line.trim().is_empty()
+            {
+                continue;
+            }
+
+            let mut columns = line.split(',').map(|column| column.trim());
+
+            let (Some(x), Some(category), Some(y)) = (columns.next(), columns.next(), columns.next())
+            else
+            {
+                return Err(ParseError::MissingColumns{line: line_number}.into());
+            };
+
+            let parse = |column, value: &str|
+            {
+                value.parse::<f64>().map_err(|_|
+                {
+                    ParseError::InvalidNumber{line: line_number, column, value: value.to_owned()}
+                })
+            };
+
+            let pos = Point2{x: parse("x", x)?, y: parse("y", y)?};
+            let weight = columns.next().map(|weight| parse("weight", weight)).transpose()?.unwrap_or(1.0);
+
+            if columns.next().is_some()
+            {
+                return Err(ParseError::ExtraColumns{line: line_number}.into());
+            }
+
+            let graph = if let Some(index) = graphs.iter().position(|(name, _)| name == category)
+            {
+                &mut graphs[index].1
+            } else
+            {
+                let graph = self.graph_builder()
+                    .with_name(category);
+
+                graphs.push((category.to_owned(), graph));
+
+                &mut graphs.last_mut().unwrap().1
+            };
+
+            graph.push(PointType{color: None, pos, weight});
+        }
+
+        self.graphs.extend(graphs.into_iter().map(|(_, graph)| graph.complete()));
+        self.recompute_bounds();
+
+        Ok(())
+    }
+
+    // only depends on the current graphs, so the order they were added in doesnt matter
+    pub fn recompute_bounds(&mut self)
+    {
+        let left = self.config.x_range.map(|(start, _)| start).unwrap_or(0.0);
+
+        self.left = left;
+        self.right = self.graphs.iter().filter_map(|graph| graph.last())
+            .fold(left, |right, last| right.max(last.pos.x));
+
+        let has_points = self.graphs.iter().any(|graph| graph.last().is_some());
+
+        self.top = match self.config.max_height
+        {
+            Some(max_height) if has_points => max_height,
+            _ =>
+            {
+                self.graphs.iter().flat_map(|graph| graph.points_slice())
+                    .fold(0.0, |top, point| top.max(point.pos.y))
+            }
+        };
+
+        self.bottom = match self.config.min_height
+        {
+            Some(min_height) if !self.graphs.is_empty() => min_height,
+            _ =>
+            {
+                self.graphs.iter().filter_map(|graph| self.graph_bottom(graph))
+                    .fold(f64::MAX, f64::min)
+            }
+        };
+    }
+
+    // the lowest point, pushed further down by min_avg if its set
+    fn graph_bottom(&self, graph: &Graph) -> Option<f64>
+    {
+        let points = graph.points_slice();
+
+        let lowest = points.iter().map(|point| point.pos.y).reduce(f64::min)?;
+
+        let bottom = if let Some(scale) = self.config.min_avg
+        {
+            let average = points.iter().map(|point| point.pos.y).sum::<f64>() / points.len() as f64;
+
+            let diff = (average - lowest).abs();
+
+            lowest - (diff * scale)
+        } else
+        {
+            lowest
+        };
+
+        Some(bottom)
+    }
+
+    // everything the drawer would use, without drawing anything
+    pub fn describe(&self) -> GraphSummary
+    {
+        let series = self.graphs.iter().map(|graph|
+        {
+            let points = graph.points();
+
+            let count = points.len();
+
+            SeriesSummary{
+                name: graph.name().map(|name| name.to_owned()),
+                count,
+                lowest: graph.lowest(),
+                highest: graph.highest(),
+                mean: (count > 0).then(|| points.mean_y()),
+                correlation: (count > 2).then(|| points.pearson_corr_coeff())
+            }
+        }).collect();
+
+        GraphSummary{
+            series,
+            bounds: self.bounds()
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox
+    {
+        BoundingBox{
+            bottom_left: Point2{x: self.left, y: self.bottom},
+            top_right: Point2{x: self.right, y: self.top}
+        }
+    }
+
+    // png for .png paths, ppm for anything else, same as PPMImage::save
+    #[allow(dead_code)]
+    pub fn save(&self, size: Point2<usize>, path: impl AsRef<Path>) -> io::Result<()>
+    {
+        self.to_image(size).save(path)
+    }
+
+    #[allow(dead_code)]
+    pub fn save_with_format(
+        &self,
+        size: Point2<usize>,
+        path: impl AsRef<Path>,
+        format: ImageFormat
+    ) -> io::Result<()>
+    {
+        self.to_image(size).save_with_format(path, format)
+    }
+
+    // same as to_image but errors instead of making up colors with strict_palette
+    pub fn to_image_checked(&self, size: Point2<usize>) -> Result<PPMImage, ConfigError>
+    {
+        if self.config.strict_palette
+        {
+            let colors = self.config.palette_kind.colors().len();
+
+            let series = self.graphs.iter().filter(|graph|
+            {
+                !(self.config.color_by_name && graph.name().is_some())
+            }).count();
+
+            if series > colors
+            {
+                return Err(ConfigError::PaletteTooSmall{series, colors});
+            }
+        }
+
+        Ok(self.to_image(size))
+    }
+
+    pub fn to_image(&self, size: Point2<usize>) -> PPMImage
+    {
+        let image = PPMImage::new(size.x, size.y, Color::white());
+
+        self.to_drawer_with(image).to_image()
+    }
+
+    // draws over the whole image, size is taken from it
+    #[allow(dead_code)]
+    pub fn render_into(&self, image: &mut PPMImage)
+    {
+        image.clear(Color::white());
+
+        let taken = mem::replace(image, PPMImage::new(0, 0, Color::white()));
+
+        *image = self.to_drawer_with(taken).to_image();
+    }
+
+    // the series percent_difference asks for, none if either index is past the last graph
+    pub fn percent_difference(&self) -> Option<Points>
+    {
+        let (a, b) = self.config.percent_difference?;
+
+        let a = &self.graphs.get(a)?.0.points;
+        let b = &self.graphs.get(b)?.0.points;
+
+        Some(a.percent_difference(b))
+    }
+
+    pub fn to_drawer_with(&self, image: PPMImage) -> GrapherDrawer<'_>
+    {
+        self.to_drawer_within(image, self.bounds())
+    }
+
+    // draws as if the data went over bounds instead of its own
+    fn to_drawer_within(&self, image: PPMImage, bounds: BoundingBox) -> GrapherDrawer<'_>
+    {
+        let width = image.width();
+        let height = image.height();
+
+        let aspect = width as f64 / height as f64;
+        let pad = 0.025;
+
+        // room for the axis titles
+        let x_title = title_band(&self.config.x_label);
+        let y_title = title_band(&self.config.y_label);
+
+        let difference_axis = if self.config.percent_difference.is_some()
+        {
+            DIFFERENCE_AXIS_BAND
+        } else
+        {
+            0.0
+        };
+
+        let mut pad = Padding{
+            bottom_left: Point2{x: (0.2 + y_title) / aspect, y: pad + x_title},
+            top_right: Point2{x: 1.0 - (pad + difference_axis) / aspect, y: 1.0 - pad}
+        };
+
+        if self.config.equal_aspect
+        {
+            // shrink whichever axis has less data per pixel so both end up the same
+            let area = pad.top_right - pad.bottom_left;
+
+            let per_pixel = bounds.area() / (area * Point2{x: width as f64, y: height as f64});
+
+            if per_pixel.x > per_pixel.y
+            {
+                pad.top_right.y = pad.bottom_left.y + area.y * (per_pixel.y / per_pixel.x);
+            } else if per_pixel.y > per_pixel.x
+            {
+                pad.top_right.x = pad.bottom_left.x + area.x * (per_pixel.x / per_pixel.y);
+            }
+        }
+
+        GrapherDrawer::new_within(self, image, pad, bounds)
+    }
+}
+
+// lays out graphers left to right, top to bottom, each one in its own cell
+pub struct Grid
+{
+    columns: usize,
+    rows: usize,
+    shared_scale: bool,
+    graphers: Vec<Grapher>
+}
+
+#[allow(dead_code)]
+impl Grid
+{
+    pub fn new(columns: usize, rows: usize) -> Self
+    {
+        Self{
+            columns: columns.max(1),
+            rows: rows.max(1),
+            shared_scale: false,
+            graphers: Vec::new()
+        }
+    }
+
+    // every cell uses the bounds of all the graphers together
+    pub fn shared_scale(mut self, state: bool) -> Self
+    {
+        self.shared_scale = state;
+
+        self
+    }
+
+    // graphers past the last cell dont get drawn
+    pub fn push(&mut self, grapher: Grapher)
+    {
+        self.graphers.push(grapher);
+    }
+
+    pub fn save(&self, size: Point2<usize>, path: impl AsRef<Path>) -> io::Result<()>
+    {
+        self.render(size).save(path)
+    }
+
+    pub fn render(&self, size: Point2<usize>) -> PPMImage
+    {
+        let cell_size = Point2{x: size.x / self.columns, y: size.y / self.rows};
+
+        let mut image = PPMImage::new(size.x, size.y, Color::white());
+
+        // too small to fit a single pixel per cell
+        if cell_size.x == 0 || cell_size.y == 0
+        {
+            return image;
+        }
+
+        let shared_bounds = self.shared_scale.then(||
+        {
+            self.graphers.iter().map(|grapher| grapher.bounds()).reduce(|a, b| a.union(&b))
+        }).flatten();
+
+        let cells = self.columns * self.rows;
+        self.graphers.iter().take(cells).enumerate().for_each(|(index, grapher)|
+        {
+            let cell = Point2{x: index % self.columns, y: index / self.columns};
+
+            let bounds = shared_bounds.unwrap_or_else(|| grapher.bounds());
+            let cell_image = PPMImage::new(cell_size.x, cell_size.y, Color::white());
+
+            image.blit(grapher.to_drawer_within(cell_image, bounds).to_image(), cell * cell_size);
+        });
+
+        image
+    }
+}
+
+pub struct GrapherDrawer<'a>
+{
+    grapher: &'a Grapher,
+    image: PPMImage,
+    pad: Padding,
+    bounds: BoundingBox
+}
+
+impl<'a> GrapherDrawer<'a>
+{
+    #[allow(dead_code)]
+    pub fn new(grapher: &'a Grapher, image: PPMImage, pad: Padding) -> Self
+    {
+        Self::new_within(grapher, image, pad, grapher.bounds())
+    }
+
+    fn new_within(grapher: &'a Grapher, image: PPMImage, pad: Padding, bounds: BoundingBox) -> Self
+    {
+        Self{grapher, image, pad, bounds}
+    }
+
+    #[allow(dead_code)]
+    pub fn image_mut(&mut self) -> &mut PPMImage
+    {
+        &mut self.image
+    }
+
+    pub fn to_local(&self, point: Point2<f64>) -> Point2<f64>
+    {
+        self.fit(self.position(point))
+    }
+
+    pub fn position(&self, point: Point2<f64>) -> Point2<f64>
+    {
+        let x = (point.x - self.bounds.bottom_left.x) / (self.bounds.top_right.x - self.bounds.bottom_left.x);
+        let y = (point.y - self.bounds.bottom_left.y) / (self.bounds.top_right.y - self.bounds.bottom_left.y);
+
+        let y = if let Some(scale) = self.grapher.config.log_scale
+        {
+            y.powf(scale)
+        } else
+        {
+            y
+        };
+
+        let y = if self.grapher.config.invert_y
+        {
+            1.0 - y
+        } else
+        {
+            y
+        };
+
+        Point2{x, y}
+    }
+
+    // creative names
+    pub fn unposition(&self, point: Point2<f64>) -> Point2<f64>
+    {
+        let Point2{x, y} = point;
+
+        let y = if self.grapher.config.invert_y
+        {
+            1.0 - y
+        } else
+        {
+            y
+        };
+
+        let y = if let Some(scale) = self.grapher.config.log_scale
+        {
+            y.powf(scale.recip())
+        } else
+        {
+            y
+        };
+
+        let x = x * (self.bounds.top_right.x - self.bounds.bottom_left.x);
+        let y = y * (self.bounds.top_right.y - self.bounds.bottom_left.y);
+
+        let x = x + self.bounds.bottom_left.x;
+        let y = y + self.bounds.bottom_left.y;
+
+        Point2{x, y}
+    }
+
+    pub fn fit(&self, point: Point2<f64>) -> Point2<f64>
+    {
+        point * self.pad.area() + self.pad.bottom_left
+    }
+
+    #[allow(clippy::wrong_self_convention)]
+    pub fn to_image(mut self) -> PPMImage
+    {
+        let thickness = 0.005;
+
+        let guide_size = 0.01;
+        let border_color = Color::black();
+
+        if let Some((x0, x1)) = self.grapher.config.fit_range
+        {
+            let x0 = self.position(Point2{x: x0, y: 0.0}).x.clamp(0.0, 1.0);
+            let x1 = self.position(Point2{x: x1, y: 0.0}).x.clamp(0.0, 1.0);
+
+            let highlight = BoundingBox{
+                bottom_left: self.fit(Point2{x: x0.min(x1), y: 0.0}),
+                top_right: self.fit(Point2{x: x0.max(x1), y: 1.0})
+            };
+
+            self.image.fill(highlight, Color{r: 240, g: 240, b: 250});
+        }
+
+        if !self.grapher.config.grid_on_top
+        {
+            self.draw_grid(thickness, guide_size, border_color);
+        }
+        
+        let c = Color{r: 210, g: 210, b: 210};
+        for graph in self.selected_graphs().map(|(_, graph)| graph)
+        {
+            if let Some(lowest) = graph.lowest()
+            {
+                let left = Point2{x: 0.0, y: lowest};
+                let right = Point2{x: 0.0, y: lowest};
+
+                let left = Point2{x: 0.0, y: self.position(left).y};
+                let right = Point2{x: 1.0, y: self.position(right).y};
+
+                self.image.line_thick(
+                    self.fit(left),
+                    self.fit(right),
+                    thickness,
+                    c
+                );
+            }
+        }
+        
+        self.draw_borders(thickness, border_color);
+
+        let series_colors = self.grapher.graphs.iter().enumerate().map(|(index, graph)|
+        {
+            if self.grapher.config.color_by_name
+            {
+                if let Some(name) = graph.name()
+                {
+                    return Color::from_name_hash(name);
+                }
+            }
+
+            series_color(index, &self.grapher.config)
+        }).collect::<Vec<_>>();
+
+        // stable sort so equal z keeps the parse order
+        let mut draw_order = self.selected_graphs().map(|(index, _)| index).collect::<Vec<_>>();
+        draw_order.sort_by_key(|index|
+        {
+            self.grapher.config.z_order.get(*index).copied().unwrap_or(0)
+        });
+
+        // fills go under every line so they dont tint the graphs drawn before them
+        if self.grapher.config.fill_area
+        {
+            for index in draw_order.iter().copied()
+            {
+                self.draw_area(self.grapher.graphs[index].points_slice(), series_colors[index]);
+            }
+        }
+
+        for index in draw_order
+        {
+            let graph = &self.grapher.graphs[index];
+            let color = series_colors[index];
+
+            if self.grapher.config.plot_line
+            {
+                self.draw_best_fit_line(graph, thickness, ColorAlpha{a: 100, ..color.into()});
+            }
+
+            if let Some(range) = self.grapher.config.fit_range
+            {
+                let c = Color::black().lerp(color, 0.6);
+
+                self.draw_range_fit_line(graph, range, thickness * 1.5, c.into());
+            }
+
+            self.draw_graph(graph, thickness, color);
+        }
+
+        if self.grapher.config.grid_on_top
+        {
+            self.draw_grid(thickness, guide_size, border_color);
+        }
+
+        if self.grapher.config.mark_extremes
+        {
+            let mut placed = Vec::new();
+            for (index, graph) in self.selected_graphs()
+            {
+                self.draw_extremes(graph, thickness, series_colors[index], &mut placed);
+            }
+        }
+
+        if self.grapher.config.last_value
+        {
+            for (index, graph) in self.selected_graphs()
+            {
+                self.draw_last_value(graph, series_colors[index]);
+            }
+        }
+
+        if self.grapher.config.label_indices
+        {
+            for (_, graph) in self.selected_graphs()
+            {
+                self.draw_indices(graph, thickness);
+            }
+        }
+
+        if let Some(at) = self.grapher.config.crosshair
+        {
+            self.crosshair(at, Color{r: 60, g: 60, b: 60});
+        }
+
+        if let Some(difference) = self.grapher.percent_difference()
+        {
+            self.draw_percent_difference(&difference, thickness, guide_size, Color{r: 60, g: 60, b: 60});
+        }
+
+        self.draw_units(guide_size, Color::black());
+        self.draw_axis_titles(Color::black());
+
+        if self.grapher.config.grayscale
+        {
+            self.image.grayscale();
+        }
+
+        self.image
+    }
+
+    // lines through the whole plot area at a data point, parts outside of the plot are skipped
+    pub fn crosshair(&mut self, at: Point2<f64>, c: impl ColorRepr)
+    {
+        let thickness = 0.003;
+
+        let position = self.position(at);
+
+        let inside = |value: f64| (0.0..=1.0).contains(&value);
+
+        if inside(position.y)
+        {
+            self.image.line_thick(
+                self.fit(Point2{x: 0.0, y: position.y}),
+                self.fit(Point2{x: 1.0, y: position.y}),
+                thickness,
+                c
+            );
+        }
+
+        if inside(position.x)
+        {
+            self.image.line_thick(
+                self.fit(Point2{x: position.x, y: 0.0}),
+                self.fit(Point2{x: position.x, y: 1.0}),
+                thickness,
+                c
+            );
+        }
+
+        if inside(position.x) && inside(position.y)
+        {
+            self.image.circle(self.fit(position), thickness * 3.0, c);
+        }
+    }
+
+    fn draw_extremes(
+        &mut self,
+        graph: &Graph,
+        thickness: f64,
+        c: Color,
+        placed: &mut Vec<BoundingBox>
+    )
+    {
+        let points = graph.points_slice();
+
+        let compare_y = |a: &&PointType, b: &&PointType|
+        {
+            a.pos.y.partial_cmp(&b.pos.y).expect("values must be comparable")
+        };
+
+        let mut marked: Vec<PointType> = Vec::new();
+        [
+            points.iter().min_by(compare_y),
+            points.iter().max_by(compare_y),
+            points.last()
+        ].into_iter().flatten().for_each(|point|
+        {
+            // the last point is often also the min or max
+            if !marked.iter().any(|other| other.pos == point.pos)
+            {
+                marked.push(*point);
+            }
+        });
+
+        let label_height = 0.03;
+        let label_width = label_height * 6.0 / self.image.aspect();
+
+        for point in marked
+        {
+            if !self.is_segment_visible(point.pos, point.pos)
+            {
+                continue;
+            }
+
+            let local = self.to_local(point.pos);
+
+            self.image.circle(local, thickness * 3.0, Color::black());
+            self.image.circle(local, thickness * 2.0, c);
+
+            let offset = thickness * 3.0;
+
+            let fits_right = local.x + offset + label_width < self.pad.top_right.x;
+            let fits_above = local.y + offset + label_height < self.pad.top_right.y;
+
+            let left = if fits_right { local.x + offset } else { local.x - offset - label_width };
+            let bottom = if fits_above { local.y + offset } else { local.y - offset - label_height };
+
+            let label_bb = BoundingBox{
+                bottom_left: Point2{x: left, y: bottom},
+                top_right: Point2{x: left + label_width, y: bottom + label_height}
+            };
+
+            // dont draw labels on top of each other
+            if placed.iter().any(|other| other.intersects(&label_bb))
+            {
+                continue;
+            }
+
+            placed.push(label_bb);
+
+            let align_h = if fits_right { TextHAlign::Left } else { TextHAlign::Right };
+
+            self.image.text_between(
+                &self.grapher.config.font,
+                Color::black(),
+                label_bb,
+                align_h,
+                TextVAlign::Middle,
+                &format!("{:.*}", self.grapher.config.y_precision, point.pos.y)
+            );
+        }
+    }
+
+    // a box with the newest value at the right edge, level with the last point
+    fn draw_last_value(&mut self, graph: &Graph, c: Color)
+    {
+        let Some(last) = graph.last() else { return };
+
+        if !self.is_segment_visible(last.pos, last.pos)
+        {
+            return;
+        }
+
+        let label_height = 0.04;
+        let label_width = label_height * 6.0 / self.image.aspect();
+
+        let local = self.to_local(last.pos);
+
+        let bottom_left = Point2{
+            x: self.pad.top_right.x - label_width,
+            y: local.y - label_height * 0.5
+        };
+
+        // keep the whole box on the image
+        let bottom_left = bottom_left.clamp(
+            Point2::repeat(0.0),
+            Point2{x: 1.0 - label_width, y: 1.0 - label_height}
+        );
+
+        let label_bb = BoundingBox{
+            bottom_left,
+            top_right: bottom_left + Point2{x: label_width, y: label_height}
+        };
+
+        self.image.fill(label_bb, c);
+        self.image.rect_outline(label_bb, 0.002, Color::black());
+
+        let pad = label_height * 0.15;
+        let text_bb = BoundingBox{
+            bottom_left: label_bb.bottom_left + Point2::repeat(pad),
+            top_right: label_bb.top_right - Point2::repeat(pad)
+        };
+
+        self.image.text_between(
+            &self.grapher.config.font,
+            c.contrasting(),
+            text_bb,
+            TextHAlign::Middle,
+            TextVAlign::Middle,
+            &format!("{:.*}", self.grapher.config.y_precision, last.pos.y)
+        );
+    }
+
+    // the position in the series next to every point, returns the labels and where they went
+    fn draw_indices(&mut self, graph: &Graph, thickness: f64) -> Vec<(String, BoundingBox)>
+    {
+        let points = graph.points_slice();
+
+        if points.len() > INDEX_LABEL_LIMIT
+        {
+            return Vec::new();
+        }
+
+        let mut labels = Vec::new();
+
+        let label_height = 0.025;
+        let label_width = label_height * 3.0 / self.image.aspect();
+
+        for (index, point) in points.iter().enumerate()
+        {
+            if !self.is_segment_visible(point.pos, point.pos)
+            {
+                continue;
+            }
+
+            let local = self.to_local(point.pos);
+
+            let offset = thickness * 2.0;
+
+            // flip to the other side near the edges of the plot
+            let fits_right = local.x + offset + label_width < self.pad.top_right.x;
+            let fits_above = local.y + offset + label_height < self.pad.top_right.y;
+
+            let left = if fits_right { local.x + offset } else { local.x - offset - label_width };
+            let bottom = if fits_above { local.y + offset } else { local.y - offset - label_height };
+
+            let label_bb = BoundingBox{
+                bottom_left: Point2{x: left, y: bottom},
+                top_right: Point2{x: left + label_width, y: bottom + label_height}
+            };
+
+            let align_h = if fits_right { TextHAlign::Left } else { TextHAlign::Right };
+            let align_v = if fits_above { TextVAlign::Bottom } else { TextVAlign::Top };
+
+            let label = index.to_string();
+
+            self.image.text_between(
+                &self.grapher.config.font,
+                Color::black(),
+                label_bb,
+                align_h,
+                align_v,
+                &label
+            );
+
+            labels.push((label, label_bb));
+        }
+
+        labels
+    }
+
+    // returns the boxes of the labels that got drawn
+    fn draw_units(
+        &mut self,
+        guide_size: f64,
+        c: Color
+    ) -> Vec<BoundingBox>
+    {
+        let precision = self.grapher.config.y_precision;
+
+        // bottom text ecks dee
+        let bottom_text = format!("{:.*}", precision, self.unposition(Point2{x: 0.0, y: 0.0}).y);
+        let top_text = format!("{:.*}", precision, self.unposition(Point2{x: 0.0, y: 1.0}).y);
+
+        let aspect = self.image.aspect();
+
+        let margin = 0.02 / aspect;
+
+        // the axis titles push the labels away from the edges
+        let bottom_left = Point2{
+            x: margin + title_band(&self.grapher.config.y_label) / aspect,
+            y: self.pad.bottom_left.y
+        };
+
+        let max_height = 0.05;
+
+        let right_edge = self.pad.bottom_left.x - margin - guide_size;
+
+        let bottom_bb = self.image.text_between(
+            &self.grapher.config.font,
+            c,
+            BoundingBox{
+                bottom_left,
+                top_right: Point2{
+                    x: right_edge,
+                    y: max_height + title_band(&self.grapher.config.x_label)
+                }
+            },
+            TextHAlign::Right,
+            TextVAlign::Bottom,
+            &bottom_text
+        );
+
+        let bottom_left = Point2{
+            y: self.pad.top_right.y - max_height,
+            ..bottom_left
+        };
+
+        let top_bb = self.image.text_between(
+            &self.grapher.config.font,
+            c,
+            BoundingBox{
+                bottom_left,
+                top_right: Point2{
+                    x: right_edge,
+                    y: self.pad.top_right.y
+                }
+            },
+            TextHAlign::Right,
+            TextVAlign::Top,
+            &top_text
+        );
+
+        let mut placed = vec![bottom_bb, top_bb];
+
+        // custom ticks replace the evenly spaced labels
+        let label_values = if let Some(ticks) = self.grapher.config.y_ticks.as_ref()
+        {
+            let (bottom, top) = (self.bounds.bottom_left.y, self.bounds.top_right.y);
+
+            ticks.iter().filter(|tick| (bottom..=top).contains(*tick)).map(|tick|
+            {
+                (self.position(Point2{x: self.bounds.bottom_left.x, y: *tick}).y, *tick)
+            }).collect::<Vec<_>>()
+        } else
+        {
+            let labels = self.grapher.config.y_labels;
+
+            (1..=labels).map(|i|
+            {
+                let value = i as f64 / (labels + 1) as f64;
+
+                (value, self.unposition(Point2{x: 0.0, y: value}).y)
+            }).collect()
+        };
+
+        let mut unit_at = |value: f64, this_value: f64|
+        {
+            let this_text = format!("{this_value:.precision$}");
+
+            let half_max = max_height * 0.5;
+            let y = self.pad.bottom_left.y
+                + (self.pad.top_right.y - self.pad.bottom_left.y) * value;
+
+            let bb = BoundingBox{
+                bottom_left: Point2{
+                    x: bottom_left.x,
+                    y: y - half_max
+                },
+                top_right: Point2{
+                    x: right_edge,
+                    y: y + half_max
+                }
+            };
+
+            let font = &self.grapher.config.font;
+
+            let text_bb = self.image.text_between_bounds(
+                font,
+                bb,
+                TextHAlign::Right,
+                TextVAlign::Middle,
+                &this_text
+            );
+
+            // too many labels would draw over each other, skip the ones that dont fit
+            if placed.iter().any(|other| other.intersects(&text_bb))
+            {
+                return;
+            }
+
+            placed.push(text_bb);
+
+            self.image.text_between(
+                font,
+                c,
+                bb,
+                TextHAlign::Right,
+                TextVAlign::Middle,
+                &this_text
+            );
+        };
+
+        label_values.into_iter().for_each(|(value, this_value)| unit_at(value, this_value));
+
+        placed
+    }
+
+    // the x title goes under the plot, the y one reads upwards to the left of the labels
+    fn draw_axis_titles(&mut self, c: Color)
+    {
+        let aspect = self.image.aspect();
+        let (start, end) = (AXIS_TITLE_MARGIN, AXIS_TITLE_BAND - AXIS_TITLE_MARGIN);
+
+        if let Some(label) = self.grapher.config.x_label.as_ref()
+        {
+            self.image.text_between(
+                &self.grapher.config.font,
+                c,
+                BoundingBox{
+                    bottom_left: Point2{x: self.pad.bottom_left.x, y: start},
+                    top_right: Point2{x: self.pad.top_right.x, y: end}
+                },
+                TextHAlign::Middle,
+                TextVAlign::Middle,
+                label
+            );
+        }
+
+        if let Some(label) = self.grapher.config.y_label.as_ref()
+        {
+            let height = self.image.height() as f64;
+
+            // drawn sideways on its own image and then turned upright
+            let length = ((self.pad.top_right.y - self.pad.bottom_left.y) * height) as usize;
+            let thickness = ((end - start) * height) as usize;
+
+            if length == 0 || thickness == 0
+            {
+                return;
+            }
+
+            let mut title = PPMImage::new(length, thickness, Color::white());
+            title.text_between(
+                &self.grapher.config.font,
+                c,
+                BoundingBox{bottom_left: Point2::repeat(0.0), top_right: Point2::repeat(1.0)},
+                TextHAlign::Middle,
+                TextVAlign::Middle,
+                label
+            );
+
+            let position = Point2{
+                x: (start / aspect) * self.image.width() as f64,
+                y: (1.0 - self.pad.top_right.y) * height
+            };
+
+            self.image.blit(title.rotate_left(), position.cast());
+        }
+    }
+
+    fn draw_graph(
+        &mut self,
+        graph: &Graph,
+        thickness: f64,
+        c: Color
+    )
+    {
+        let points = graph.points_slice();
+
+        let line_points = self.line_points(points);
+        let local_points = line_points.iter().map(|point| self.to_local(*point)).collect::<Vec<_>>();
+
+        let pairs = (0..line_points.len().saturating_sub(1)).filter(|index|
+        {
+            self.is_segment_visible(line_points[*index], line_points[index + 1])
+        }).flat_map(|index|
+        {
+            let curve = self.curve_between(&local_points, index);
+
+            curve.iter().copied().zip(curve.iter().copied().skip(1)).collect::<Vec<_>>()
+        }).collect::<Vec<_>>();
+
+        if self.grapher.config.shadow
+        {
+            let shadow_pixels = 3.0;
+            let offset = Point2{
+                x: shadow_pixels / self.image.width() as f64,
+                y: -shadow_pixels / self.image.height() as f64
+            };
+
+            let shadow_c = ColorAlpha{a: 50, ..Color::black().into()};
+
+            for (input, output) in &pairs
+            {
+                self.image.line_thick(
+                    *input + offset,
+                    *output + offset,
+                    thickness,
+                    shadow_c
+                );
+            }
+        }
+
+        if self.grapher.config.sdf_lines
+        {
+            let mut drawer = self.image.sdf_drawer();
+
+            pairs.into_iter().for_each(|(input, output)|
+            {
+                drawer.line(input, output, thickness, c);
+            });
+
+            drawer.submit();
+        } else
+        {
+            for (input, output) in pairs
+            {
+                self.image.line_thick(
+                    input,
+                    output,
+                    thickness,
+                    c
+                );
+            }
+        }
+
+        if self.grapher.config.show_points
+        {
+            let stride = self.grapher.config.marker_stride.max(1);
+            for point in points.iter().step_by(stride)
+            {
+                if !self.is_segment_visible(point.pos, point.pos)
+                {
+                    continue;
+                }
+
+                let point_color = point.color.unwrap_or(ColorAlpha{r: 0, g: 0, b: 0, a: 90}.set(c));
+
+                // area grows with the weight
+                let radius = if self.grapher.config.weight_markers
+                {
+                    thickness * 1.5 * point.weight.max(0.0).sqrt()
+                } else
+                {
+                    thickness * 1.5
+                };
+
+                self.image.circle(
+                    self.to_local(point.pos),
+                    radius,
+                    point_color
+                );
+            }
+        }
+
+        if self.grapher.config.mark_plateaus
+        {
+            let marker_c = Color::black().lerp(c, 0.5);
+            let half_height = thickness * 3.0;
+
+            for (start, end) in graph.points().plateaus()
+            {
+                for point in [points[start], points[end]]
+                {
+                    if !self.is_segment_visible(point.pos, point.pos)
+                    {
+                        continue;
+                    }
+
+                    let local = self.to_local(point.pos);
+
+                    self.image.line_thick(
+                        Point2{y: local.y - half_height, ..local},
+                        Point2{y: local.y + half_height, ..local},
+                        thickness * 0.75,
+                        marker_c
+                    );
+                }
+            }
+        }
+
+        let averages = graph.averages();
+        if let Some(values) = averages
+        {
+            let values = values.iter().zip(points).map(|(value, point)|
+            {
+                Point2{
+                    x: point.pos.x,
+                    y: *value
+                }
+            });
+
+            let average_pairs = values.clone().zip(values.skip(1));
+
+            let avg_c = ColorAlpha{
+                a: 100,
+                ..Color::white().lerp(c, 0.6).into()
+            };
+
+            for (input, output) in average_pairs
+            {
+                if input.y.is_nan() || output.y.is_nan() || !self.is_segment_visible(input, output)
+                {
+                    continue;
+                }
+
+                self.image.line_thick(
+                    self.to_local(input),
+                    self.to_local(output),
+                    thickness,
+                    avg_c
+                );
+            }
+        }
+    }
+
+    fn selected_graphs(&self) -> impl Iterator<Item=(usize, &'a Graph)>
+    {
+        let grapher = self.grapher;
+
+        grapher.graphs.iter().enumerate().filter(move |(index, graph)|
+        {
+            grapher.config.select.as_ref().map(|select|
+            {
+                select.iter().any(|selector| selector.matches(*index, graph))
+            }).unwrap_or(true)
+        })
+    }
+
+    // the positions the line goes through, step mode adds a corner between every pair
+    fn line_points(&self, points: &[PointType]) -> Vec<Point2<f64>>
+    {
+        let positions = points.iter().map(|point| point.pos);
+
+        let Some(step) = self.grapher.config.step
+        else
+        {
+            return positions.collect();
+        };
+
+        let mut result: Vec<Point2<f64>> = Vec::with_capacity(points.len() * 2);
+        for pos in positions
+        {
+            if let Some(previous) = result.last().copied()
+            {
+                let corner = match step
+                {
+                    StepMode::HorizontalFirst => Point2{x: pos.x, y: previous.y},
+                    StepMode::VerticalFirst => Point2{x: previous.x, y: pos.y}
+                };
+
+                result.push(corner);
+            }
+
+            result.push(pos);
+        }
+
+        result
+    }
+
+    // the line from the point at index to the next one, a catmull-rom curve when smoothing
+    fn curve_between(&self, points: &[Point2<f64>], index: usize) -> Vec<Point2<f64>>
+    {
+        let (p1, p2) = (points[index], points[index + 1]);
+
+        // stairs have to stay stairs
+        if !self.grapher.config.smooth || self.grapher.config.step.is_some()
+        {
+            return vec![p1, p2];
+        }
+
+        // the ends repeat themselves so the curve starts and stops on the data
+        let p0 = points[index.saturating_sub(1)];
+        let p3 = points[(index + 2).min(points.len() - 1)];
+
+        let curve = self.image.bezier_cubic_points(p1, p1 + (p2 - p0) / 6.0, p2 - (p3 - p1) / 6.0, p2);
+
+        // the curve can overshoot past the data, which shouldnt end up outside the plot
+        curve.into_iter().map(|point| point.clamp(self.pad.bottom_left, self.pad.top_right)).collect()
+    }
+
+    // fills between the line and the bottom edge
+    fn draw_area(&mut self, points: &[PointType], c: Color)
+    {
+        let line_points = self.line_points(points);
+
+        let (Some(first), Some(last)) = (line_points.first(), line_points.last())
+        else
+        {
+            return;
+        };
+
+        let inside = |this: &Self, point: Point2<f64>|
+        {
+            this.fit(point.clamp(Point2::repeat(0.0), Point2::repeat(1.0)))
+        };
+
+        let bottom_at = |this: &Self, point: Point2<f64>|
+        {
+            inside(this, Point2{x: this.position(point).x, y: 0.0})
+        };
+
+        let top = line_points.iter().map(|point| inside(self, self.position(*point))).collect::<Vec<_>>();
+
+        let curve = (0..top.len().saturating_sub(1)).flat_map(|index|
+        {
+            let curve = self.curve_between(&top, index);
+
+            // the next curve starts where this one ends
+            curve.into_iter().skip(usize::from(index != 0))
+        });
+
+        let polygon = iter::once(bottom_at(self, *first))
+            .chain(curve)
+            .chain(iter::once(bottom_at(self, *last)))
+            .collect::<Vec<_>>();
+
+        self.image.polygon(&polygon, ColorAlpha{a: 60, ..c.into()});
+    }
+
+    // only skips segments that are fully outside on one side, the rest get drawn
+    fn is_segment_visible(&self, a: Point2<f64>, b: Point2<f64>) -> bool
+    {
+        let a = self.position(a);
+        let b = self.position(b);
+
+        let outside = |a: f64, b: f64|
+        {
+            (a < 0.0 && b < 0.0) || (a > 1.0 && b > 1.0)
+        };
+
+        !(outside(a.x, b.x) || outside(a.y, b.y))
+    }
+
+    fn draw_grid(&mut self, thickness: f64, guide_size: f64, border_color: Color)
+    {
+        let c = ColorAlpha{
+            a: self.grapher.config.guide_alpha,
+            ..Color::black().into()
+        };
+
+        self.draw_guides(thickness * 0.75, guide_size, border_color, c);
+    }
+
+    fn draw_guides(
+        &mut self,
+        original_thickness: f64,
+        guide_size: f64,
+        border_color: Color,
+        c: ColorAlpha
+    )
+    {
+        let cap_at = |image: &mut PPMImage, y: f64, thickness: f64|
+        {
+            let y = self.pad.bottom_left.lerp(self.pad.top_right, y).y;
+
+            let thickness_ratio = thickness / original_thickness;
+            let guide_width = guide_size * thickness_ratio.sqrt();
+            image.line_thick(
+                Point2{x: self.pad.bottom_left.x - guide_width, y},
+                Point2{x: self.pad.bottom_left.x + guide_width, y},
+                thickness,
+                border_color
+            );
+        };
+
+        let line_at = |image: &mut PPMImage, y: f64, thickness: f64|
+        {
+            {
+                let y = self.pad.bottom_left.lerp(self.pad.top_right, y).y;
+
+                image.line_thick(
+                    Point2{x: self.pad.bottom_left.x, y},
+                    Point2{x: self.pad.top_right.x, y},
+                    thickness,
+                    c
+                );
+            }
+
+            cap_at(image, y, thickness);
+        };
+
+        let half_thickness = original_thickness * 0.55;
+
+        line_at(&mut self.image, 0.5, original_thickness);
+        line_at(&mut self.image, 1.0, original_thickness);
+
+        let divisions = 10;
+        let step = 0.5 / divisions as f64;
+        for i in 1..divisions
+        {
+            line_at(&mut self.image, i as f64 * step, half_thickness);
+            line_at(&mut self.image, 0.5 + i as f64 * step, half_thickness);
+        }
+
+        cap_at(&mut self.image, 0.0, original_thickness);
+        cap_at(&mut self.image, 1.0, original_thickness);
+    }
+
+    fn positioned_points(&self, graph: &Graph) -> Points
     {
-        let image = PPMImage::new(size.x, size.y, Color::white());
+        graph.points().clone().map(|x|
+        {
+            PointType{
+                pos: self.position(x.pos),
+                ..x
+            }
+        })
+    }
 
-        self.to_drawer_with(image).to_image()
+    fn draw_best_fit_line(&mut self, graph: &Graph, thickness: f64, c: ColorAlpha)
+    {
+        let line = self.positioned_points(graph).best_fit_line_weighted();
+
+        let style = LineStyle::Dashed{on: thickness * 6.0, off: thickness * 4.0};
+
+        self.draw_fit_line(line, (0.0, 1.0), thickness, c, style);
     }
 
-    pub fn to_drawer_with(&self, image: PPMImage) -> GrapherDrawer
+    fn draw_range_fit_line(
+        &mut self,
+        graph: &Graph,
+        range: (f64, f64),
+        thickness: f64,
+        c: ColorAlpha
+    )
     {
-        let width = image.width();
-        let height = image.height();
+        let x0 = self.position(Point2{x: range.0, y: 0.0}).x;
+        let x1 = self.position(Point2{x: range.1, y: 0.0}).x;
 
-        let aspect = width as f64 / height as f64;
-        let pad = 0.025;
+        let (x0, x1) = (x0.min(x1).max(0.0), x0.max(x1).min(1.0));
+        if x0 >= x1
+        {
+            return;
+        }
+
+        if let Some(line) = self.positioned_points(graph).best_fit_line_range(x0, x1)
+        {
+            self.draw_fit_line(line, (x0, x1), thickness, c, LineStyle::Solid);
+        }
+    }
+
+    // draws the line between the x positions, cut off where it leaves the graph
+    fn draw_fit_line(
+        &mut self,
+        line: Line,
+        (x0, x1): (f64, f64),
+        thickness: f64,
+        c: ColorAlpha,
+        style: LineStyle
+    )
+    {
+        let start = Point2{x: x0, y: line.at_x(x0)};
+        let end = Point2{x: x1, y: line.at_x(x1)};
+
+        let inside = BoundingBox{bottom_left: Point2::repeat(0.0), top_right: Point2::repeat(1.0)};
+
+        if let Some((start, end)) = clip_line(start, end, inside)
+        {
+            self.image.line_thick_styled(
+                self.fit(start),
+                self.fit(end),
+                thickness,
+                c,
+                style
+            );
+        }
+    }
+
+    // scaled to its own range, which always includes 0%
+    fn draw_percent_difference(
+        &mut self,
+        difference: &Points,
+        thickness: f64,
+        guide_size: f64,
+        c: Color
+    )
+    {
+        if difference.is_empty()
+        {
+            return;
+        }
+
+        let (lowest, highest) = difference.iter().fold((0.0_f64, 0.0_f64), |(lowest, highest), point|
+        {
+            (lowest.min(point.pos.y), highest.max(point.pos.y))
+        });
+
+        // a flat line still needs a range to sit in the middle of
+        let (lowest, highest) = if highest > lowest
+        {
+            (lowest, highest)
+        } else
+        {
+            (lowest - 1.0, highest + 1.0)
+        };
 
-        let pad = Padding{
-            bottom_left: Point2{x: 0.2 / aspect, y: pad},
-            top_right: Point2{x: 1.0 - pad / aspect, y: 1.0 - pad}
+        let to_plot = |drawer: &Self, point: Point2<f64>|
+        {
+            drawer.fit(Point2{
+                x: drawer.position(point).x,
+                y: (point.y - lowest) / (highest - lowest)
+            })
         };
 
-        GrapherDrawer::new(self, image, pad)
+        let zero = to_plot(self, Point2{x: 0.0, y: 0.0}).y;
+        self.image.line_thick_styled(
+            Point2{x: self.pad.bottom_left.x, y: zero},
+            Point2{x: self.pad.top_right.x, y: zero},
+            thickness * 0.5,
+            c,
+            LineStyle::Dashed{on: thickness * 4.0, off: thickness * 4.0}
+        );
+
+        let points = difference.iter()
+            .filter(|point| (0.0..=1.0).contains(&self.position(point.pos).x))
+            .map(|point| to_plot(self, point.pos))
+            .collect::<Vec<_>>();
+
+        self.image.polyline_thick(&points, thickness, c);
+
+        let margin = 0.02 / self.image.aspect();
+        let max_height = 0.05;
+
+        let left = self.pad.top_right.x + guide_size + margin;
+        let right = 1.0 - margin;
+
+        // zero gets its own label only when its not already one of the ends
+        let zero_label = (lowest < 0.0 && highest > 0.0).then_some((0.0, TextVAlign::Middle));
+
+        [Some((lowest, TextVAlign::Bottom)), zero_label, Some((highest, TextVAlign::Top))]
+            .into_iter()
+            .flatten()
+            .for_each(|(value, align_v)|
+            {
+                let y = to_plot(self, Point2{x: 0.0, y: value}).y;
+
+                let (bottom, top) = match align_v
+                {
+                    TextVAlign::Bottom => (y, y + max_height),
+                    TextVAlign::Top => (y - max_height, y),
+                    TextVAlign::Middle => (y - max_height * 0.5, y + max_height * 0.5)
+                };
+
+                let bb = BoundingBox{
+                    bottom_left: Point2{x: left, y: bottom},
+                    top_right: Point2{x: right, y: top}
+                };
+
+                self.image.text_between(
+                    &self.grapher.config.font,
+                    c,
+                    bb,
+                    TextHAlign::Left,
+                    align_v,
+                    &format!("{value:.1}%")
+                );
+            });
+    }
+
+    fn draw_borders(&mut self, thickness: f64, c: Color)
+    {
+        self.image.line_thick(
+            self.pad.bottom_left,
+            Point2{x: self.pad.bottom_left.x, y: self.pad.top_right.y},
+            thickness,
+            c
+        );
+
+        self.image.line_thick(
+            self.pad.bottom_left,
+            Point2{x: self.pad.top_right.x, y: self.pad.bottom_left.y},
+            thickness,
+            c
+        );
     }
 }
 
-pub struct GrapherDrawer<'a>
+#[cfg(test)]
+mod tests
 {
-    grapher: &'a Grapher,
-    image: PPMImage,
-    pad: Padding
-}
+    use super::*;
 
-impl<'a> GrapherDrawer<'a>
-{
-    pub fn new(grapher: &'a Grapher, image: PPMImage, pad: Padding) -> Self
+    use std::{env, fs, path::PathBuf};
+
+
+    // lib and bin tests run at the same time, so the pid keeps their files apart
+    fn temp_file(name: &str, contents: &str) -> PathBuf
     {
-        Self{grapher, image, pad}
+        let path = env::temp_dir().join(format!("graphthingy_{}_{name}", std::process::id()));
+        fs::write(&path, contents).unwrap();
+
+        path
     }
 
-    #[allow(dead_code)]
-    pub fn image_mut(&mut self) -> &mut PPMImage
+    // evenly spaced from x = 0
+    fn points_from(ys: &[f64]) -> Points
     {
-        &mut self.image
+        Points(ys.iter().enumerate().map(|(x, y)|
+        {
+            PointType{color: None, pos: Point2{x: x as f64, y: *y}, weight: 1.0}
+        }).collect())
     }
 
-    pub fn to_local(&self, point: Point2<f64>) -> Point2<f64>
+    #[test]
+    fn predict_interval_has_width()
     {
-        self.fit(self.position(point))
+        // big enough that students t needs more than the plain gamma function can hold
+        let points = Points((0..1000).map(|index|
+        {
+            let x = index as f64;
+            let noise = (x * 12.9898).sin() * 3.0;
+
+            PointType{color: None, pos: Point2{x, y: 2.0 * x + noise}, weight: 1.0}
+        }).collect());
+
+        let x = 1000.0;
+        let prediction = points.predict(x).unwrap();
+
+        assert_eq!(prediction.mean, points.best_fit_line().at_x(x));
+
+        assert!(prediction.lower < prediction.mean);
+        assert!(prediction.mean < prediction.upper);
+
+        // noise has a standard deviation around 2, so about 2 * 1.96 each way
+        let half_width = prediction.upper - prediction.mean;
+        assert!((3.0..6.0).contains(&half_width), "half width was {half_width}");
     }
 
-    pub fn position(&self, point: Point2<f64>) -> Point2<f64>
+    #[test]
+    fn running_average_uses_previous_points()
     {
-        let x = (point.x - self.grapher.left) / (self.grapher.right - self.grapher.left);
-        let y = (point.y - self.grapher.bottom) / (self.grapher.top - self.grapher.bottom);
+        let points = Points([0.0, 10.0, 10.0, 0.0, 5.0].into_iter().enumerate().map(|(x, y)|
+        {
+            PointType{color: None, pos: Point2{x: x as f64, y}, weight: 1.0}
+        }).collect());
 
-        let y = if let Some(scale) = self.grapher.config.log_scale
+        let values = RunningAverage::new(2, &points).values().to_vec();
+
+        assert!(values[0].is_nan());
+        assert_eq!(values[1..], [0.0, 5.0, 10.0, 5.0]);
+    }
+
+    #[test]
+    fn points_index_and_iter()
+    {
+        let points = points_from(&[3.0, 1.0, 2.0]);
+
+        assert_eq!(points.len(), 3);
+        assert!(!points.is_empty());
+        assert!(Points::new().is_empty());
+
+        assert_eq!(points[1].pos, Point2{x: 1.0, y: 1.0});
+        assert_eq!(points[2].pos.y, 2.0);
+
+        let ys = points.iter().map(|point| point.pos.y).collect::<Vec<_>>();
+        assert_eq!(ys, [3.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn overlapping_y_labels_get_skipped()
+    {
+        let config = GrapherConfig{y_labels: 20, ..Default::default()};
+
+        let mut builder = GraphBuilder::new(None);
+        points_from(&[0.0, 5.0, 2.0, 9.0]).iter().for_each(|point| builder.push(*point));
+
+        let grapher = Grapher::from_graphs(vec![builder.complete()], config).unwrap();
+
+        let mut drawer = grapher.to_drawer_with(PPMImage::new(100, 100, Color::white()));
+        let placed = drawer.draw_units(0.01, Color::black());
+
+        // the top and bottom labels always get drawn, 20 more cant fit in 100 pixels
+        assert!(placed.len() > 2);
+        assert!(placed.len() < 22, "{} labels got drawn", placed.len());
+
+        placed.iter().enumerate().for_each(|(index, bb)|
         {
-            y.powf(scale)
-        } else
+            assert!(placed[index + 1..].iter().all(|other| !other.intersects(bb)));
+        });
+    }
+
+    #[test]
+    fn interpolate_between_points()
+    {
+        let points = points_from(&[0.0, 10.0, 4.0]);
+
+        assert_eq!(points.interpolate(0.5), Some(5.0));
+        assert_eq!(points.interpolate(1.75), Some(5.5));
+        assert_eq!(points.interpolate(2.0), Some(4.0));
+        assert_eq!(points.interpolate(0.0), Some(0.0));
+
+        assert_eq!(points.interpolate(-0.1), None);
+        assert_eq!(points.interpolate(2.1), None);
+
+        assert_eq!(Points::new().interpolate(0.0), None);
+    }
+
+    #[test]
+    fn identical_series_differ_by_zero_percent()
+    {
+        let a = points_from(&[1.0, 3.0, 2.0, 5.0]);
+
+        let difference = a.percent_difference(&a.clone());
+
+        assert_eq!(difference.len(), a.len());
+        assert!(difference.iter().all(|point| point.pos.y == 0.0));
+
+        let graph = |points: &Points|
         {
-            y
+            let mut builder = GraphBuilder::new(None);
+            points.iter().for_each(|point| builder.push(*point));
+
+            builder.complete()
         };
 
-        Point2{x, y}
+        let config = GrapherConfig{percent_difference: Some((0, 1)), ..Default::default()};
+        let grapher = Grapher::from_graphs(vec![graph(&a), graph(&a)], config).unwrap();
+
+        let difference = grapher.percent_difference().unwrap();
+        assert!(difference.iter().all(|point| point.pos.y == 0.0));
+
+        // the flat line gets drawn without anything going wrong
+        grapher.to_image(Point2{x: 200, y: 100});
+
+        let config = GrapherConfig{percent_difference: Some((0, 2)), ..Default::default()};
+        let grapher = Grapher::from_graphs(vec![graph(&a), graph(&a)], config).unwrap();
+
+        assert!(grapher.percent_difference().is_none());
     }
 
-    // creative names
-    pub fn unposition(&self, point: Point2<f64>) -> Point2<f64>
+    #[test]
+    fn invalid_config_is_rejected()
     {
-        let Point2{x, y} = point;
+        let config = GrapherConfig{min_avg: Some(1.0), min_height: Some(0.0), ..Default::default()};
 
-        let y = if let Some(scale) = self.grapher.config.log_scale
+        assert!(matches!(Grapher::new(config), Err(ConfigError::ExclusiveOptions{..})));
+
+        let config = GrapherConfig{running_avg: Some(RunningAvgWindow::Fixed(0)), ..Default::default()};
+
+        assert!(matches!(Grapher::new(config), Err(ConfigError::ZeroRunningAvg)));
+
+        assert!(Grapher::new(GrapherConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn x_range_drops_outside_points()
+    {
+        let mut builder = GraphBuilder::new(None).with_x_range(Some((2.0, 4.0)));
+
+        (0..7).for_each(|x|
         {
-            y.powf(scale.recip())
-        } else
+            builder.push(PointType{color: None, pos: Point2{x: x as f64, y: 1.0}, weight: 1.0});
+        });
+
+        let graph = builder.complete();
+        let xs = graph.points_slice().iter().map(|point| point.pos.x).collect::<Vec<_>>();
+
+        assert_eq!(xs, [2.0, 3.0, 4.0]);
+    }
+
+    #[test]
+    fn palette_names_round_trip()
+    {
+        PaletteKind::all().for_each(|palette|
+        {
+            assert_eq!(PaletteKind::from_name(palette.name()), Some(palette));
+        });
+    }
+
+    #[test]
+    fn categories_split_into_series()
+    {
+        let path = temp_file("categories.txt", "1, a, 3\n1, b, 4\n\n2, a, 5\n3, b, 6, 2\n");
+
+        let mut grapher = Grapher::new(GrapherConfig::default()).unwrap();
+        grapher.parse_categories(&path).unwrap();
+
+        fs::remove_file(path).unwrap();
+
+        let series = grapher.graphs.iter().map(|graph|
+        {
+            let points = graph.points_slice().iter().map(|point| (point.pos.x, point.pos.y, point.weight));
+
+            (graph.name().unwrap().to_owned(), points.collect::<Vec<_>>())
+        }).collect::<Vec<_>>();
+
+        assert_eq!(series, [
+            ("a".to_owned(), vec![(1.0, 3.0, 1.0), (2.0, 5.0, 1.0)]),
+            ("b".to_owned(), vec![(1.0, 4.0, 1.0), (3.0, 6.0, 2.0)])
+        ]);
+    }
+
+    #[test]
+    fn category_errors()
+    {
+        let parse_error = |name: &str, contents: &str|
+        {
+            let path = temp_file(name, contents);
+
+            let mut grapher = Grapher::new(GrapherConfig::default()).unwrap();
+            let err = grapher.parse_categories(&path).unwrap_err();
+
+            fs::remove_file(path).unwrap();
+
+            *err.downcast::<ParseError>().unwrap()
+        };
+
+        let err = parse_error("missing.txt", "1, a, 3\n2, a\n");
+        assert!(matches!(err, ParseError::MissingColumns{line: 2}), "got {err:?}");
+
+        let err = parse_error("bad_y.txt", "1, a, 3\n\n2, a, three\n");
+        assert!(
+            matches!(&err, ParseError::InvalidNumber{line: 3, column: "y", value} if value == "three"),
+            "got {err:?}"
+        );
+
+        let err = parse_error("bad_x.txt", "x, a, 3\n");
+        assert!(matches!(err, ParseError::InvalidNumber{line: 1, column: "x", ..}), "got {err:?}");
+
+        let err = parse_error("bad_weight.txt", "1, a, 3, heavy\n");
+        assert!(matches!(err, ParseError::InvalidNumber{line: 1, column: "weight", ..}), "got {err:?}");
+
+        let err = parse_error("extra.txt", "1, a, 3\n2, a, 4, 1, 5\n");
+        assert!(matches!(err, ParseError::ExtraColumns{line: 2}), "got {err:?}");
+    }
+
+    #[test]
+    fn plain_extra_columns()
+    {
+        let path = temp_file("plain_extra.txt", "1\n2 0.5\n3 1 7\n");
+
+        let mut grapher = Grapher::new(GrapherConfig::default()).unwrap();
+        let err = grapher.parse(&path).unwrap_err();
+
+        fs::remove_file(path).unwrap();
+
+        let err = *err.downcast::<ParseError>().unwrap();
+        assert!(matches!(err, ParseError::ExtraColumns{line: 3}), "got {err:?}");
+    }
+
+    #[test]
+    fn weights_bias_fit_and_size_markers()
+    {
+        let weighted = |weights: [f64; 4]|
+        {
+            Points([0.0, 1.0, 2.0, 3.0].into_iter().zip(weights).map(|(x, weight)|
+            {
+                // the last point is way off the y = x line
+                let y = if x == 3.0 { 10.0 } else { x };
+
+                PointType{color: None, pos: Point2{x, y}, weight}
+            }).collect())
+        };
+
+        let line = weighted([1.0, 1.0, 1.0, 0.0]).best_fit_line_weighted();
+        assert!((line.slope - 1.0).abs() < 1e-9 && line.intercept.abs() < 1e-9);
+
+        let even = weighted([1.0; 4]).best_fit_line_weighted().slope;
+        let heavy = weighted([1.0, 1.0, 1.0, 10.0]).best_fit_line_weighted().slope;
+        assert!(heavy > even, "{heavy} should be steeper than {even}");
+
+        // no weight at all falls back to every point counting the same
+        let points = weighted([0.0; 4]);
+        let line = points.best_fit_line_weighted();
+        assert_eq!((line.slope, line.intercept), (points.best_fit_line().slope, points.best_fit_line().intercept));
+
+        let covered = |weights|
+        {
+            let config = GrapherConfig{show_points: true, weight_markers: true, ..Default::default()};
+
+            let mut builder = GraphBuilder::new(None);
+            weighted(weights).iter().for_each(|point| builder.push(*point));
+
+            let image = Grapher::from_graphs(vec![builder.complete()], config).unwrap()
+                .to_image(Point2{x: 200, y: 200});
+
+            (0..200).flat_map(|y| (0..200).map(move |x| Point2{x, y}))
+                .filter(|pos| image[*pos] != Color::white())
+                .count()
+        };
+
+        assert!(covered([1.0, 9.0, 1.0, 1.0]) > covered([1.0; 4]));
+    }
+
+    #[test]
+    fn rolling_apply_windows()
+    {
+        let points = points_from(&[1.0, 5.0, 2.0, 0.0, 3.0, 4.0]);
+
+        let maxes = points.rolling_apply(3, |values| values.iter().copied().fold(f64::MIN, f64::max));
+        let maxes = maxes.iter().map(|point| point.pos.y).collect::<Vec<_>>();
+
+        assert_eq!(maxes, [1.0, 5.0, 5.0, 5.0, 3.0, 4.0]);
+
+        // the way the running average used to work, over up to amount points before each one
+        let amount = 4;
+        let old = (0..points.len()).map(|x|
+        {
+            let previous = &points.0[0..x];
+            let take_amount = previous.len().min(amount);
+
+            previous.iter().rev().take(take_amount).map(|point| point.pos.y).sum::<f64>() / take_amount as f64
+        }).collect::<Vec<_>>();
+
+        let new = RunningAverage::new(amount as u32, &points).values().to_vec();
+
+        assert!(old[0].is_nan() && new[0].is_nan());
+        assert_eq!(old[1..], new[1..]);
+    }
+
+    #[test]
+    fn renders_are_byte_identical()
+    {
+        // translucent fills, shadows and fit lines all overlap here, so any order dependence shows up
+        let config = GrapherConfig{
+            running_avg: Some(RunningAvgWindow::Fixed(3)),
+            plot_line: true,
+            shadow: true,
+            fill_area: true,
+            mark_extremes: true,
+            last_value: true,
+            label_indices: true,
+            x_label: Some("time".to_owned()),
+            y_label: Some("value".to_owned()),
+            ..Default::default()
+        };
+
+        let graph = |ys: &[f64]|
         {
-            y
+            let mut builder = GraphBuilder::new(config.running_avg);
+            points_from(ys).iter().for_each(|point| builder.push(*point));
+
+            builder.complete()
         };
 
-        let x = x * (self.grapher.right - self.grapher.left);
-        let y = y * (self.grapher.top - self.grapher.bottom);
+        let graphs = vec![graph(&[1.0, 4.0, 2.0, 6.0, 3.0, 5.0]), graph(&[2.0, 3.0, 5.0, 1.0, 4.0, 4.5])];
+        let grapher = Grapher::from_graphs(graphs, config).unwrap();
 
-        let x = x + self.grapher.left;
-        let y = y + self.grapher.bottom;
+        let render = ||
+        {
+            let mut bytes = Vec::new();
+            grapher.to_image(Point2{x: 300, y: 200}).write_to(&mut bytes).unwrap();
 
-        Point2{x, y}
-    }
+            bytes
+        };
 
-    pub fn fit(&self, point: Point2<f64>) -> Point2<f64>
-    {
-        point * self.pad.area() + self.pad.bottom_left
+        let first = render();
+        (0..20).for_each(|_| assert!(render() == first));
     }
 
-    pub fn to_image(mut self) -> PPMImage
+
+    #[test]
+    fn grid_skips_tiny_cells_and_keeps_bounds()
     {
-        let thickness = 0.005;
+        let grapher = |ys: &[f64]|
+        {
+            let mut builder = GraphBuilder::new(None);
+            points_from(ys).iter().for_each(|point| builder.push(*point));
 
-        let guide_size = 0.01;
-        let border_color = Color::black();
+            Grapher::from_graphs(vec![builder.complete()], GrapherConfig::default()).unwrap()
+        };
 
-        {
-            let c = ColorAlpha{
-                a: 15,
-                ..Color::black().into()
-            };
+        let mut grid = Grid::new(2, 2).shared_scale(true);
+        grid.push(grapher(&[1.0, 3.0, 2.0]));
+        grid.push(grapher(&[-5.0, 0.0]));
+        grid.push(grapher(&[10.0, 20.0, 15.0, 30.0]));
 
-            self.draw_guides(thickness * 0.75, guide_size, border_color, c);
-        }
-        
-        let c = Color{r: 210, g: 210, b: 210};
-        for graph in &self.grapher.graphs
-        {
-            if let Some(lowest) = graph.lowest()
-            {
-                let left = Point2{x: 0.0, y: lowest};
-                let right = Point2{x: 0.0, y: lowest};
+        let before = grid.graphers.iter().map(|grapher| grapher.bounds()).collect::<Vec<_>>();
 
-                let left = Point2{x: 0.0, y: self.position(left).y};
-                let right = Point2{x: 1.0, y: self.position(right).y};
+        // a single pixel tall image has no room for a row, so nothing gets drawn
+        let image = grid.render(Point2{x: 40, y: 1});
+        assert_eq!((image.width(), image.height()), (40, 1));
+        assert!((0..40).all(|x| image[Point2{x, y: 0}] == Color::white()));
 
-                self.image.line_thick(
-                    self.fit(left),
-                    self.fit(right),
-                    thickness,
-                    c
-                );
-            }
-        }
-        
-        self.draw_borders(thickness, border_color);
+        // barely any room in a cell still shouldnt panic
+        let image = grid.render(Point2{x: 5, y: 7});
+        assert_eq!((image.width(), image.height()), (5, 7));
 
-        let mut colors = vec![
-            Color{r: 255, g: 120, b: 120},
-            Color{r: 120, g: 255, b: 120},
-            Color{r: 120, g: 120, b: 255},
-            Color{r: 255, g: 120, b: 220},
-            Color{r: 255, g: 220, b: 120}
-        ].into_iter();
+        let path = env::temp_dir().join(format!("graphthingy_{}_grid.ppm", std::process::id()));
+        grid.save(Point2{x: 200, y: 100}, &path).unwrap();
+        fs::remove_file(&path).unwrap();
 
-        let mut seed = 54321;
-        for graph in &self.grapher.graphs
+        grid.graphers.iter().zip(before).for_each(|(grapher, before)|
         {
-            let color = colors.next().unwrap_or_else(||
-            {
-                seed ^= seed << 13;
-                seed ^= seed >> 17;
-                seed ^= seed << 5;
+            let after = grapher.bounds();
 
-                let random_u32 = seed;
+            assert_eq!(after.bottom_left, before.bottom_left);
+            assert_eq!(after.top_right, before.top_right);
+        });
+    }
 
-                let r = |i: u32|
-                {
-                    ((random_u32 >> (i * 8)) & 0xff) as u8
-                };
 
-                Color{r: r(0), g: r(1), b: r(2)}
-            });
+    #[test]
+    fn save_picks_format_from_extension()
+    {
+        let mut builder = GraphBuilder::new(None);
+        points_from(&[1.0, 3.0, 2.0]).iter().for_each(|point| builder.push(*point));
 
-            if self.grapher.config.plot_line
-            {
-                self.draw_best_fit_line(graph, thickness, ColorAlpha{a: 100, ..color.into()});
-            }
+        let grapher = Grapher::from_graphs(vec![builder.complete()], GrapherConfig::default()).unwrap();
 
-            self.draw_graph(graph, thickness, color);
-        }
+        let saved_start = |name: &str|
+        {
+            let path = env::temp_dir().join(format!("graphthingy_{}_{name}", std::process::id()));
+            grapher.save(Point2{x: 50, y: 40}, &path).unwrap();
 
-        self.draw_units(guide_size, Color::black());
+            let bytes = fs::read(&path).unwrap();
+            fs::remove_file(path).unwrap();
 
-        self.image
+            bytes[..4].to_vec()
+        };
+
+        assert_eq!(saved_start("save.png"), [0x89, b'P', b'N', b'G']);
+        assert_eq!(saved_start("save.ppm"), *b"P6\n5");
+        assert_eq!(saved_start("save.unknown"), *b"P6\n5");
+        assert_eq!(saved_start("save_no_extension"), *b"P6\n5");
     }
 
-    fn draw_units(
-        &mut self,
-        guide_size: f64,
-        c: Color
-    )
+
+    #[test]
+    fn step_line_points()
     {
-        // bottom text ecks dee
-        let bottom_text = format!("{:.4}", self.grapher.bottom);
-        let top_text = format!("{:.4}", self.grapher.top);
+        let points = points_from(&[1.0, 3.0, 2.0]);
 
-        let mut bottom_left = Point2{
-            x: 0.02,
-            y: self.pad.bottom_left.y
-        };
+        let line_points = |step|
+        {
+            let config = GrapherConfig{step, ..Default::default()};
+            let grapher = Grapher::from_graphs(Vec::new(), config).unwrap();
 
-        let aspect = self.image.aspect();
+            let drawer = grapher.to_drawer_with(PPMImage::new(10, 10, Color::white()));
+            let positions = drawer.line_points(&points.0);
 
-        bottom_left.x /= aspect;
+            positions.into_iter().map(|pos| (pos.x, pos.y)).collect::<Vec<_>>()
+        };
 
-        let max_height = 0.05;
+        assert_eq!(line_points(None), [(0.0, 1.0), (1.0, 3.0), (2.0, 2.0)]);
 
-        let right_edge = self.pad.bottom_left.x - bottom_left.x - guide_size;
+        // stays at the old height until the next x, then jumps
+        assert_eq!(
+            line_points(Some(StepMode::HorizontalFirst)),
+            [(0.0, 1.0), (1.0, 1.0), (1.0, 3.0), (2.0, 3.0), (2.0, 2.0)]
+        );
 
-        self.image.text_between(
-            &self.grapher.config.font,
-            c,
-            BoundingBox{
-                bottom_left,
-                top_right: Point2{
-                    x: right_edge,
-                    y: max_height
-                }
-            },
-            TextHAlign::Right,
-            TextVAlign::Bottom,
-            &bottom_text
+        assert_eq!(
+            line_points(Some(StepMode::VerticalFirst)),
+            [(0.0, 1.0), (0.0, 3.0), (1.0, 3.0), (1.0, 2.0), (2.0, 2.0)]
         );
+    }
 
-        let bottom_left = Point2{
-            y: self.pad.top_right.y - max_height,
-            ..bottom_left
-        };
 
-        self.image.text_between(
-            &self.grapher.config.font,
-            c,
-            BoundingBox{
-                bottom_left,
-                top_right: Point2{
-                    x: right_edge,
-                    y: self.pad.top_right.y
-                }
-            },
-            TextHAlign::Right,
-            TextVAlign::Top,
-            &top_text
-        );
+    #[test]
+    fn builder_tracks_extremes()
+    {
+        let mut builder = GraphBuilder::new(Some(RunningAvgWindow::Fixed(2))).with_x_range(Some((1.0, 5.0)));
 
-        let mut unit_at = |value|
+        assert!(builder.is_empty());
+        assert_eq!((builder.lowest(), builder.highest()), (None, None));
+
+        // the -100 and 100 are outside the x range so they never count
+        let pushes = [(0.0, -100.0), (1.0, 4.0), (2.0, -2.0), (3.0, 7.0), (4.0, 1.0), (6.0, 100.0)];
+        pushes.into_iter().for_each(|(x, y)|
         {
-            let this_value = self.unposition(Point2{x: 0.0, y: value}).y;
-            let this_text = format!("{this_value:.4}");
+            builder.push(PointType{color: None, pos: Point2{x, y}, weight: 1.0});
+        });
 
-            let half_max = max_height * 0.5;
-            let y = self.pad.bottom_left.y
-                + (self.pad.top_right.y - self.pad.bottom_left.y) * value;
+        assert_eq!(builder.len(), 4);
+        assert_eq!((builder.lowest(), builder.highest()), (Some(-2.0), Some(7.0)));
 
-            self.image.text_between(
-                &self.grapher.config.font,
-                c,
-                BoundingBox{
-                    bottom_left: Point2{
-                        x: bottom_left.x,
-                        y: y - half_max
-                    },
-                    top_right: Point2{
-                        x: right_edge,
-                        y: y + half_max
-                    }
-                },
-                TextHAlign::Right,
-                TextVAlign::Middle,
-                &this_text
-            );
-        };
+        let graph = builder.complete();
+        assert_eq!((graph.lowest(), graph.highest()), (Some(-2.0), Some(7.0)));
 
-        unit_at(0.25);
-        unit_at(0.5);
-        unit_at(0.75);
+        let averages = graph.averages().unwrap();
+        assert!(averages[0].is_nan());
+        assert_eq!(averages[1..], [4.0, 1.0, 2.5]);
     }
 
-    fn draw_graph(
-        &mut self,
-        graph: &Graph,
-        thickness: f64,
-        c: Color
-    )
+
+    #[test]
+    fn smooth_curve_hits_points_and_stays_inside()
     {
-        let points = graph.points_slice();
-        let pairs = points.iter().zip(points.iter().skip(1));
+        let mut builder = GraphBuilder::new(None);
+        points_from(&[0.0, 0.0, 10.0, 10.0, 3.0]).iter().for_each(|point| builder.push(*point));
 
-        for (input, output) in pairs
-        {
-            self.image.line_thick(
-                self.to_local(input.pos),
-                self.to_local(output.pos),
-                thickness,
-                c
-            );
-        }
+        let config = GrapherConfig{smooth: true, ..Default::default()};
+        let grapher = Grapher::from_graphs(vec![builder.complete()], config).unwrap();
 
-        for point in points
-        {
-            let point_color = point.color.unwrap_or(ColorAlpha{r: 0, g: 0, b: 0, a: 90}.set(c));
+        let drawer = grapher.to_drawer_with(PPMImage::new(200, 100, Color::white()));
 
-            self.image.circle(
-                self.to_local(point.pos),
-                thickness * 1.5,
-                point_color
-            );
-        }
+        let points = grapher.graphs[0].points_slice();
+        let local = drawer.line_points(points).into_iter().map(|point| drawer.to_local(point)).collect::<Vec<_>>();
 
-        let averages = graph.averages();
-        if let Some(values) = averages
+        let inside = |point: Point2<f64>|
         {
-            let values = values.iter().zip(points).map(|(value, point)|
-            {
-                Point2{
-                    x: point.pos.x,
-                    y: *value
-                }
-            });
+            let (low, high) = (drawer.pad.bottom_left, drawer.pad.top_right);
 
-            let average_pairs = values.clone().zip(values.skip(1));
+            (low.x..=high.x).contains(&point.x) && (low.y..=high.y).contains(&point.y)
+        };
 
-            let avg_c = ColorAlpha{
-                a: 100,
-                ..Color::white().lerp(c, 0.6).into()
-            };
+        (0..local.len() - 1).for_each(|index|
+        {
+            let curve = drawer.curve_between(&local, index);
 
-            for (input, output) in average_pairs
-            {
-                self.image.line_thick(
-                    self.to_local(input),
-                    self.to_local(output),
-                    thickness,
-                    avg_c
-                );
-            }
-        }
-    }
+            assert_eq!(curve.first(), Some(&local[index]));
+            assert_eq!(curve.last(), Some(&local[index + 1]));
 
-    fn draw_guides(
-        &mut self,
-        original_thickness: f64,
-        guide_size: f64,
-        border_color: Color,
-        c: ColorAlpha
-    )
-    {
-        let lerp = |a: f64, b: f64, t: f64|
-        {
-            a * (1.0 - t) + b * t
-        };
+            // flat then straight up makes the catmull-rom curve dip below the lowest point
+            assert!(curve.iter().all(|point| inside(*point)), "curve {index} leaves the plot");
+        });
+    }
 
-        let cap_at = |image: &mut PPMImage, y: f64, thickness: f64|
-        {
-            let y = lerp(self.pad.bottom_left.y, self.pad.top_right.y, y);
 
-            let thickness_ratio = thickness / original_thickness;
-            let guide_width = guide_size * thickness_ratio.sqrt();
-            image.line_thick(
-                Point2{x: self.pad.bottom_left.x - guide_width, y},
-                Point2{x: self.pad.bottom_left.x + guide_width, y},
-                thickness,
-                border_color
-            );
-        };
+    #[test]
+    fn bounds_dont_depend_on_order()
+    {
+        let a = temp_file("order_a.txt", "5\n1\n8\n");
+        let b = temp_file("order_b.txt", "-3\n20\n4\n6\n2\n");
 
-        let line_at = |image: &mut PPMImage, y: f64, thickness: f64|
+        let bounds = |paths: [&PathBuf; 2]|
         {
-            {
-                let y = lerp(self.pad.bottom_left.y, self.pad.top_right.y, y);
+            let config = GrapherConfig{min_avg: Some(0.5), ..Default::default()};
 
-                image.line_thick(
-                    Point2{x: self.pad.bottom_left.x, y},
-                    Point2{x: self.pad.top_right.x, y},
-                    thickness,
-                    c
-                );
-            }
+            let mut grapher = Grapher::new(config).unwrap();
+            paths.into_iter().for_each(|path| grapher.parse(path).unwrap());
 
-            cap_at(image, y, thickness);
+            grapher.bounds()
         };
 
-        let half_thickness = original_thickness * 0.55;
+        let forward = bounds([&a, &b]);
+        let backward = bounds([&b, &a]);
 
-        line_at(&mut self.image, 0.5, original_thickness);
-        line_at(&mut self.image, 1.0, original_thickness);
+        fs::remove_file(a).unwrap();
+        fs::remove_file(b).unwrap();
 
-        let divisions = 10;
-        let step = 0.5 / divisions as f64;
-        for i in 1..divisions
-        {
-            line_at(&mut self.image, i as f64 * step, half_thickness);
-            line_at(&mut self.image, 0.5 + i as f64 * step, half_thickness);
-        }
+        assert_eq!(forward.bottom_left, backward.bottom_left);
+        assert_eq!(forward.top_right, backward.top_right);
 
-        cap_at(&mut self.image, 0.0, original_thickness);
-        cap_at(&mut self.image, 1.0, original_thickness);
+        // min_avg pushes the bottom past the lowest point
+        assert!(forward.bottom_left.y < -3.0);
     }
 
-    fn draw_best_fit_line(&mut self, graph: &Graph, thickness: f64, c: ColorAlpha)
+
+    #[test]
+    fn x_title_centered_under_plot()
     {
-        let line = graph.points().clone().map(|x|
-        {
-            PointType{
-                pos: self.position(x.pos),
-                ..x
-            }
-        }).best_fit_line();
+        let mut builder = GraphBuilder::new(None);
+        points_from(&[1.0, 3.0, 2.0]).iter().for_each(|point| builder.push(*point));
 
-        let point_at = |x|
-        {
-            let x = x;
-            let y = line.at_x(x);
+        let config = GrapherConfig{x_label: Some("time".to_owned()), ..Default::default()};
+        let grapher = Grapher::from_graphs(vec![builder.complete()], config).unwrap();
 
-            if !(0.0..=1.0).contains(&y)
-            {
-                let y = y.clamp(0.0, 1.0);
+        let (width, height) = (400, 300);
 
-                Point2{x: line.at_y(y), y}
-            } else
-            {
-                Point2{x, y}
-            }
-        };
+        let drawer = grapher.to_drawer_with(PPMImage::new(width, height, Color::white()));
+        let pad = drawer.pad;
 
-        let start = point_at(0.0);
-        let end = point_at(1.0);
+        let image = drawer.to_image();
 
-        self.image.line_thick(
-            self.fit(start),
-            self.fit(end),
-            thickness,
-            c
-        );
+        // the rows the title band covers, y goes down in the image
+        let rows = ((1.0 - AXIS_TITLE_BAND) * height as f64) as usize..height;
+
+        let xs = rows.flat_map(|y| (0..width).map(move |x| Point2{x, y}))
+            .filter(|pos| image[*pos] != Color::white())
+            .map(|pos| pos.x)
+            .collect::<Vec<_>>();
+
+        assert!(!xs.is_empty());
+
+        let left = *xs.iter().min().unwrap() as f64;
+        let right = *xs.iter().max().unwrap() as f64 + 1.0;
+
+        let plot_middle = (pad.bottom_left.x + pad.top_right.x) / 2.0 * width as f64;
+        let title_middle = (left + right) / 2.0;
+
+        assert!((plot_middle - title_middle).abs() < 2.0, "title at {title_middle}, plot at {plot_middle}");
+        assert!(left > pad.bottom_left.x * width as f64);
     }
 
-    fn draw_borders(&mut self, thickness: f64, c: Color)
+
+    #[test]
+    fn index_labels_count_up()
     {
-        self.image.line_thick(
-            self.pad.bottom_left,
-            Point2{x: self.pad.bottom_left.x, y: self.pad.top_right.y},
-            thickness,
-            c
-        );
+        let mut builder = GraphBuilder::new(None);
+        points_from(&[1.0, 3.0, 2.0]).iter().for_each(|point| builder.push(*point));
 
-        self.image.line_thick(
-            self.pad.bottom_left,
-            Point2{x: self.pad.top_right.x, y: self.pad.bottom_left.y},
-            thickness,
-            c
-        );
+        let grapher = Grapher::from_graphs(vec![builder.complete()], GrapherConfig::default()).unwrap();
+
+        let (width, height) = (300, 200);
+        let mut drawer = grapher.to_drawer_with(PPMImage::new(width, height, Color::white()));
+
+        let labels = drawer.draw_indices(&grapher.graphs[0], 0.005);
+        assert_eq!(labels.iter().map(|(label, _)| label.as_str()).collect::<Vec<_>>(), ["0", "1", "2"]);
+
+        // only the labels have been drawn so far
+        let image = &drawer.image;
+        labels.iter().zip(grapher.graphs[0].points_slice()).for_each(|((label, bb), point)|
+        {
+            let local = drawer.to_local(point.pos);
+            assert!((bb.bottom_left.x - local.x).abs() < 0.1, "label {label} is far from its point");
+
+            let low = Point2{x: bb.bottom_left.x * width as f64, y: (1.0 - bb.top_right.y) * height as f64};
+            let high = Point2{x: bb.top_right.x * width as f64, y: (1.0 - bb.bottom_left.y) * height as f64};
+
+            let drawn = (low.y as usize..high.y as usize).any(|y|
+            {
+                (low.x as usize..high.x as usize).any(|x| image[Point2{x, y}] != Color::white())
+            });
+
+            assert!(drawn, "label {label} left its box empty");
+        });
     }
 }
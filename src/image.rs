@@ -1,14 +1,17 @@
 use std::{
     f64,
     mem,
-    io::{self, Write},
+    io::{self, Read, Write},
     fs::File,
     path::Path,
-    collections::HashSet,
+    collections::{HashSet, VecDeque},
     ops::{Index, IndexMut}
 };
 
-use crate::{Font, FontChar, Point2};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+
+use crate::{png, Font, FontChar, Point2};
 
 
 pub trait ColorRepr: Copy
@@ -24,7 +27,7 @@ impl ColorRepr for ()
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Color
 {
     pub r: u8,
@@ -45,6 +48,68 @@ impl Color
         Self{r: 255, g: 255, b: 255}
     }
 
+    pub fn all_named() -> impl Iterator<Item=(&'static str, Self)>
+    {
+        [
+            ("black", Self::black()),
+            ("white", Self::white()),
+            ("gray", Self{r: 128, g: 128, b: 128}),
+            ("red", Self{r: 255, g: 0, b: 0}),
+            ("green", Self{r: 0, g: 255, b: 0}),
+            ("blue", Self{r: 0, g: 0, b: 255}),
+            ("yellow", Self{r: 255, g: 255, b: 0}),
+            ("cyan", Self{r: 0, g: 255, b: 255}),
+            ("magenta", Self{r: 255, g: 0, b: 255}),
+            ("orange", Self{r: 255, g: 165, b: 0}),
+            ("purple", Self{r: 128, g: 0, b: 128}),
+            ("brown", Self{r: 165, g: 42, b: 42}),
+            ("pink", Self{r: 255, g: 192, b: 203})
+        ].into_iter()
+    }
+
+    pub fn from_name(name: &str) -> Option<Self>
+    {
+        Self::all_named().find(|(this_name, _)| *this_name == name).map(|(_, color)| color)
+    }
+
+    // hue in degrees, saturation and value in 0..1
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self
+    {
+        let hue = hue.rem_euclid(360.0) / 60.0;
+
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - ((hue % 2.0) - 1.0).abs());
+
+        let (r, g, b) = match hue as u32
+        {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x)
+        };
+
+        let m = value - chroma;
+        let to_u8 = |c: f32|
+        {
+            ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8
+        };
+
+        Self{r: to_u8(r), g: to_u8(g), b: to_u8(b)}
+    }
+
+    // fnv-1a so the same name gets the same color between runs
+    pub fn from_name_hash(name: &str) -> Self
+    {
+        let hash = name.bytes().fold(0xcbf29ce484222325_u64, |hash, byte|
+        {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+
+        Self::from_hsv((hash % 360) as f32, 0.55, 0.95)
+    }
+
     pub fn gradient_lerp(gradient: &[Self], amount: f32) -> Self
     {
         let colors_amount = gradient.len();
@@ -56,6 +121,25 @@ impl Color
         gradient[amount_lower].lerp(gradient[amount_lower + 1], amount - amount_lower as f32)
     }
 
+    // component wise average, black if theres nothing to mix
+    pub fn mix(colors: &[Self]) -> Self
+    {
+        if colors.is_empty()
+        {
+            return Self::black();
+        }
+
+        let sum = colors.iter().fold([0_u32; 3], |[r, g, b], color|
+        {
+            [r + color.r as u32, g + color.g as u32, b + color.b as u32]
+        });
+
+        let amount = colors.len() as u32;
+        let average = |value: u32| (value / amount) as u8;
+
+        Self{r: average(sum[0]), g: average(sum[1]), b: average(sum[2])}
+    }
+
     pub fn lerp(self, other: Self, amount: f32) -> Self
     {
         Self{
@@ -69,6 +153,30 @@ impl Color
     {
         ((a as f32) * (1.0 - lerp) + (b as f32) * lerp) as u8
     }
+
+    pub fn luminance(self) -> f32
+    {
+        0.299 * self.r as f32 + 0.587 * self.g as f32 + 0.114 * self.b as f32
+    }
+
+    // black or white, whichever is easier to read on top of this color
+    pub fn contrasting(self) -> Self
+    {
+        if self.luminance() > 140.0
+        {
+            Self::black()
+        } else
+        {
+            Self::white()
+        }
+    }
+
+    pub fn to_gray(self) -> Self
+    {
+        let value = self.luminance().round().min(u8::MAX as f32) as u8;
+
+        Self{r: value, g: value, b: value}
+    }
 }
 
 impl From<ColorAlpha> for Color
@@ -91,7 +199,7 @@ impl ColorRepr for Color
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ColorAlpha
 {
     pub r: u8,
@@ -167,9 +275,10 @@ impl SignedDistance
         self.point -= translation;
     }
 
+    // rotates the shape, so the point gets rotated the opposite way
     pub fn rotate(&mut self, rotation: f64)
     {
-        self.point = self.point.rotate(rotation);
+        self.point = self.point.rotate(-rotation);
     }
 
     pub fn scale(&mut self, scale: Point2<f64>)
@@ -179,7 +288,7 @@ impl SignedDistance
 
     pub fn circle(&self, size: f64) -> f64
     {
-        self.point.x.hypot(self.point.y) - size
+        self.point.magnitude() - size
     }
 
     pub fn rectangle(&self, size: f64) -> f64
@@ -187,7 +296,7 @@ impl SignedDistance
         let dist = self.point.abs() - size;
 
         let n_dist = Point2{x: dist.x.max(0.0), y: dist.y.max(0.0)};
-        let out_dist = n_dist.x.hypot(n_dist.y);
+        let out_dist = n_dist.magnitude();
 
         let in_dist = dist.x.max(dist.y).min(0.0);
 
@@ -201,7 +310,7 @@ pub struct Line
     p0: Point2<f64>,
     p1: Point2<f64>,
     thickness: f64,
-    c: Color,
+    c: ColorAlpha,
     rotation: f64,
     half_length: f64,
     local_length: f64,
@@ -216,21 +325,29 @@ pub struct DeferredSDFDrawer<'a>
 
 impl<'a> DeferredSDFDrawer<'a>
 {
-    pub fn line(&mut self, p0: Point2<f64>, p1: Point2<f64>, thickness: f64, c: Color)
+    pub fn line(
+        &mut self,
+        p0: Point2<f64>,
+        p1: Point2<f64>,
+        thickness: f64,
+        c: impl Into<ColorAlpha>
+    )
     {
+        let c = c.into();
+
         let p0 = self.image.with_aspect(p0);
         let p1 = self.image.with_aspect(p1);
 
         let p_offset = p1 - p0;
 
         let rotation = p_offset.y.atan2(p_offset.x);
-        let length = p_offset.x.hypot(p_offset.y);
+        let length = p_offset.magnitude();
 
         let half_length = length / 2.0;
         let local_length = half_length / thickness;
         
         let clip_distance =
-            (p_offset.x.powi(2) + p_offset.y.powi(2))
+            p_offset.magnitude_squared()
             + 2.0 * length * thickness
             + thickness.powi(2);
 
@@ -255,6 +372,7 @@ impl<'a> DeferredSDFDrawer<'a>
 
 struct CharInfo<'a>
 {
+    character: char,
     size: Point2<f64>,
     position: Point2<f64>,
     thickness: f64,
@@ -288,6 +406,20 @@ impl BoundingBox<f64>
     {
         self.top_right - self.bottom_left
     }
+
+    pub fn union(&self, other: &Self) -> Self
+    {
+        Self{
+            bottom_left: self.bottom_left.min(other.bottom_left),
+            top_right: self.top_right.max(other.top_right)
+        }
+    }
+
+    pub fn intersects(&self, other: &Self) -> bool
+    {
+        (self.bottom_left.x < other.top_right.x) && (other.bottom_left.x < self.top_right.x)
+            && (self.bottom_left.y < other.top_right.y) && (other.bottom_left.y < self.top_right.y)
+    }
 }
 
 impl<T> BoundingBox<T>
@@ -303,6 +435,94 @@ impl<T> BoundingBox<T>
     }
 }
 
+// liang-barsky, returns the part of the segment inside the box if theres any
+pub fn clip_line(
+    p0: Point2<f64>,
+    p1: Point2<f64>,
+    bb: BoundingBox<f64>
+) -> Option<(Point2<f64>, Point2<f64>)>
+{
+    let diff = p1 - p0;
+
+    let edges = [
+        (-diff.x, p0.x - bb.bottom_left.x),
+        (diff.x, bb.top_right.x - p0.x),
+        (-diff.y, p0.y - bb.bottom_left.y),
+        (diff.y, bb.top_right.y - p0.y)
+    ];
+
+    let mut t0 = 0.0_f64;
+    let mut t1 = 1.0_f64;
+
+    for (p, q) in edges
+    {
+        if p == 0.0
+        {
+            // parallel to this edge and outside of it
+            if q < 0.0
+            {
+                return None;
+            }
+
+            continue;
+        }
+
+        let t = q / p;
+        if p < 0.0
+        {
+            t0 = t0.max(t);
+        } else
+        {
+            t1 = t1.min(t);
+        }
+
+        if t0 > t1
+        {
+            return None;
+        }
+    }
+
+    Some((p0 + diff * t0, p0 + diff * t1))
+}
+
+// lengths are in the same units as the line thickness, anything under a pixel counts as one pixel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineStyle
+{
+    Solid,
+    Dashed{on: f64, off: f64},
+    Dotted{spacing: f64}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeMode
+{
+    Nearest,
+    Bilinear
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat
+{
+    Ppm,
+    Png
+}
+
+impl ImageFormat
+{
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self>
+    {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+
+        match extension.as_ref()
+        {
+            "ppm" => Some(Self::Ppm),
+            "png" => Some(Self::Png),
+            _ => None
+        }
+    }
+}
+
 pub struct PPMImage
 {
     data: Vec<Color>,
@@ -346,200 +566,712 @@ impl PPMImage
         self.width as f64 / self.height as f64
     }
 
-    pub fn blit(&mut self, other: Self, position: Point2<usize>)
+    pub fn clear(&mut self, c: Color)
     {
-        for ty in 0..other.height
-        {
-            for tx in 0..other.width
-            {
-                let local = Point2{x: tx, y: ty};
-                self.get_mut(position + local).map(|p|
-                {
-                    *p = other[local]
-                });
-            }
-        }
+        self.data.fill(c);
     }
 
-    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()>
+    pub fn grayscale(&mut self)
     {
-        if self.width == 0 || self.height == 0
+        self.data.iter_mut().for_each(|c| *c = c.to_gray());
+    }
+
+    // gaussian with radius as the sigma in pixels, edges repeat the border pixels
+    pub fn blur(&mut self, radius: f64)
+    {
+        if radius.is_nan() || radius <= 0.0 || self.data.is_empty()
         {
-            panic!("cant save a 0 sized image");
+            return;
         }
 
-        let mut f = File::create(path)?;
+        // anything past 3 sigma barely contributes
+        let reach = (radius * 3.0).ceil() as isize;
 
-        let header = format!("P6\n{} {}\n255\n", self.width, self.height);
+        let kernel = (-reach..=reach).map(|distance|
+        {
+            (-((distance * distance) as f64) / (2.0 * radius * radius)).exp()
+        }).collect::<Vec<_>>();
 
-        f.write_all(header.as_bytes())?;
+        let total: f64 = kernel.iter().sum();
+        let kernel = kernel.into_iter().map(|weight| (weight / total) as f32).collect::<Vec<_>>();
 
-        let data = self.data.iter().flat_map(|c| [c.r, c.g, c.b]).collect::<Vec<u8>>();
-        f.write_all(&data)
-    }
+        let (width, height) = (self.width as isize, self.height as isize);
 
-    pub fn text_between(
-        &mut self,
-        font: &Font,
-        color: impl ColorRepr,
-        bb: BoundingBox,
-        align_h: TextHAlign,
-        align_v: TextVAlign,
-        text: &str
-    )
-    {
-        let text_size = self.text_size(font, Point2::repeat(1.0), text);
+        let pass = |values: &[[f32; 3]], step: Point2<isize>| -> Vec<[f32; 3]>
+        {
+            (0..height).flat_map(|y| (0..width).map(move |x| Point2{x, y})).map(|pos|
+            {
+                kernel.iter().zip(-reach..=reach).fold([0.0; 3], |mut acc, (weight, offset)|
+                {
+                    let sample = pos + step * offset;
 
-        let goal_size = bb.top_right - bb.bottom_left;
+                    let x = sample.x.clamp(0, width - 1);
+                    let y = sample.y.clamp(0, height - 1);
 
-        let size = goal_size / text_size;
+                    let value = values[(x + y * width) as usize];
+                    acc.iter_mut().zip(value).for_each(|(acc, value)| *acc += value * weight);
 
-        let scale = if size.x > size.y
-        {
-            Point2{
-                x: size.y / size.x,
-                y: 1.0
-            }
-        } else
-        {
-            Point2{
-                x: 1.0,
-                y: size.x / size.y,
-            }
+                    acc
+                })
+            }).collect()
         };
 
-        let real_size = goal_size * scale;
+        let values = self.data.iter().map(|c| [c.r as f32, c.g as f32, c.b as f32]).collect::<Vec<_>>();
 
-        // keep aspect ratio
-        let size = size.x.min(size.y);
+        let horizontal = pass(&values, Point2{x: 1, y: 0});
+        let vertical = pass(&horizontal, Point2{x: 0, y: 1});
 
-        let pos = match align_h
+        self.data.iter_mut().zip(vertical).for_each(|(c, [r, g, b])|
         {
-            TextHAlign::Left => bb.bottom_left,
-            TextHAlign::Middle =>
-            {
-                Point2{
-                    x: (bb.bottom_left.x + bb.top_right.x - real_size.x) * 0.5,
-                    y: bb.bottom_left.y
-                }
-            },
-            TextHAlign::Right =>
+            let channel = |value: f32| value.round().clamp(0.0, 255.0) as u8;
+
+            *c = Color{r: channel(r), g: channel(g), b: channel(b)};
+        });
+    }
+
+    pub fn blit(&mut self, other: Self, position: Point2<usize>)
+    {
+        for ty in 0..other.height
+        {
+            for tx in 0..other.width
             {
-                Point2{
-                    x: bb.top_right.x - real_size.x,
-                    y: bb.bottom_left.y
+                let local = Point2{x: tx, y: ty};
+                if let Some(p) = self.get_mut(position + local)
+                {
+                    *p = other[local];
                 }
             }
+        }
+    }
+
+    // pixel coordinates like blit, covers bottom_left up to but not including top_right
+    pub fn crop(&self, bb: BoundingBox<usize>) -> Self
+    {
+        let start = Point2{x: bb.bottom_left.x.min(self.width), y: bb.bottom_left.y.min(self.height)};
+        let end = Point2{
+            x: bb.top_right.x.clamp(start.x, self.width),
+            y: bb.top_right.y.clamp(start.y, self.height)
         };
 
-        let pos = match align_v
+        let size = end - start;
+
+        let mut image = Self::new(size.x, size.y, Color::black());
+        for y in 0..size.y
         {
-            TextVAlign::Bottom => pos,
-            TextVAlign::Middle =>
-            {
-                Point2{
-                    x: bb.bottom_left.x,
-                    y: (bb.bottom_left.y + bb.top_right.y - real_size.y) * 0.5
-                }
-            },
-            TextVAlign::Top =>
+            for x in 0..size.x
             {
-                Point2{
-                    x: bb.bottom_left.x,
-                    y: bb.top_right.y - real_size.y
-                }
+                let local = Point2{x, y};
+
+                image[local] = self[start + local];
             }
-        };
+        }
 
-        self.text(font, color, pos, Point2::repeat(size), text);
+        image
     }
 
-    pub fn text_size(
-        &mut self,
-        font: &Font,
-        size: Point2<f64>,
-        text: &str
-    ) -> Point2<f64>
+    // 4 connected, uses a queue so big regions cant blow the stack
+    pub fn flood_fill(&mut self, start: Point2<usize>, replacement: Color)
     {
-        // good enough
-        self.text(font, (), Point2::repeat(0.0), size, text).top_right
+        let target = if let Some(c) = self.get(start)
+        {
+            c
+        } else
+        {
+            return;
+        };
+
+        if target == replacement
+        {
+            return;
+        }
+
+        self[start] = replacement;
+
+        let mut queue = VecDeque::from([start]);
+        while let Some(pos) = queue.pop_front()
+        {
+            let neighbors = [
+                pos.x.checked_sub(1).map(|x| Point2{x, y: pos.y}),
+                Some(Point2{x: pos.x + 1, y: pos.y}),
+                pos.y.checked_sub(1).map(|y| Point2{x: pos.x, y}),
+                Some(Point2{x: pos.x, y: pos.y + 1})
+            ];
+
+            neighbors.into_iter().flatten().for_each(|neighbor|
+            {
+                if let Some(c) = self.get_mut(neighbor)
+                {
+                    if *c == target
+                    {
+                        *c = replacement;
+                        queue.push_back(neighbor);
+                    }
+                }
+            });
+        }
     }
 
-    pub fn text(
-        &mut self,
-        font: &Font,
-        color: impl ColorRepr,
-        position: Point2<f64>,
-        size: Point2<f64>,
-        text: &str
-    ) -> BoundingBox
+    pub fn resize(&self, new: Point2<usize>, mode: ResizeMode) -> Self
     {
-        let mut bb = BoundingBox{
-            bottom_left: position,
-            top_right: position
+        let mut image = Self::new(new.x, new.y, Color::black());
+
+        if self.data.is_empty()
+        {
+            return image;
+        }
+
+        let scale = Point2{
+            x: self.width as f64 / new.x as f64,
+            y: self.height as f64 / new.y as f64
         };
 
-        self.text_char_positions(font, position, size, text)
-            .for_each(|CharInfo{size, position, thickness, c}|
+        let highest = Point2{x: self.width - 1, y: self.height - 1};
+
+        for y in 0..new.y
+        {
+            for x in 0..new.x
             {
-                c.lines().iter().map(|line|
-                {
-                    let to_local = |mut p: Point2<f64>|
-                    {
-                        p.x *= c.width();
+                let local = Point2{x, y};
 
-                        position + p * size
-                    };
+                // pixel centers line up between the two sizes
+                let center = (local.cast::<f64>() + 0.5) * scale;
 
-                    self.line_thick_pixels(
-                        to_local(line.start),
-                        to_local(line.end),
-                        thickness
-                    )
-                }).reduce(|acc, x|
+                image[local] = match mode
                 {
-                    acc.union(&x).copied().collect::<HashSet<_>>()
-                }).map(|pixels|
-                {
-                    pixels.into_iter().for_each(|pixel|
+                    ResizeMode::Nearest =>
                     {
-                        self[pixel] = color.set(self[pixel]);
-                    });
-                });
+                        let pos = center.cast::<usize>();
 
-                bb.top_right = Point2{
-                    x: position.x + (c.width() * size.x),
-                    y: bb.top_right.y.max(bb.bottom_left.y + size.y)
+                        self[Point2{x: pos.x.min(highest.x), y: pos.y.min(highest.y)}]
+                    },
+                    ResizeMode::Bilinear =>
+                    {
+                        let pos = (center - 0.5).max(Point2::repeat(0.0));
+
+                        let low = pos.cast::<usize>();
+                        let low = Point2{x: low.x.min(highest.x), y: low.y.min(highest.y)};
+                        let high = Point2{x: (low.x + 1).min(highest.x), y: (low.y + 1).min(highest.y)};
+
+                        let fraction = (pos - low.cast::<f64>()).cast::<f32>();
+
+                        let top = self[low].lerp(self[Point2{x: high.x, y: low.y}], fraction.x);
+                        let bottom = self[Point2{x: low.x, y: high.y}].lerp(self[high], fraction.x);
+
+                        top.lerp(bottom, fraction.y)
+                    }
                 };
-            });
+            }
+        }
 
-        bb
+        image
     }
 
-    fn text_char_positions<'a, 'b>(
-        &'b mut self,
-        font: &'a Font,
-        mut position: Point2<f64>,
-        size: Point2<f64>,
-        text: &'a str
-    ) -> impl Iterator<Item=CharInfo<'a>> + 'a
+    // a quarter turn counter clockwise
+    pub fn rotate_left(&self) -> Self
     {
-        let thickness = size.x.min(size.y) * 0.05;
+        let mut image = Self::new(self.height, self.width, Color::black());
+
+        for y in 0..self.height
+        {
+            for x in 0..self.width
+            {
+                image[Point2{x: y, y: self.width - 1 - x}] = self[Point2{x, y}];
+            }
+        }
+
+        image
+    }
+
+    // opacity goes from 0 (invisible) to 1 (same as blit)
+    pub fn blit_alpha(&mut self, other: &Self, position: Point2<usize>, opacity: f32)
+    {
+        for ty in 0..other.height
+        {
+            for tx in 0..other.width
+            {
+                let local = Point2{x: tx, y: ty};
+                if let Some(p) = self.get_mut(position + local)
+                {
+                    *p = p.lerp(other[local], opacity);
+                }
+            }
+        }
+    }
+
+    // layers get drawn in order, anything outside of size gets clipped
+    pub fn composite(
+        size: Point2<usize>,
+        background: Color,
+        layers: &[(PPMImage, f32, Point2<usize>)]
+    ) -> Self
+    {
+        let mut image = Self::new(size.x, size.y, background);
+
+        layers.iter().for_each(|(layer, opacity, position)|
+        {
+            image.blit_alpha(layer, *position, *opacity);
+        });
+
+        image
+    }
+
+    // reads back a binary p6 ppm, 16 bit ones get scaled down to 8 bits
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self>
+    {
+        let mut bytes = Vec::new();
+        File::open(path)?.read_to_end(&mut bytes)?;
+
+        Self::from_ppm_bytes(&bytes)
+    }
+
+    fn from_ppm_bytes(bytes: &[u8]) -> io::Result<Self>
+    {
+        let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_owned());
+
+        let mut index = 0;
+        let mut next_token = ||
+        {
+            loop
+            {
+                match bytes.get(index)
+                {
+                    Some(b'#') =>
+                    {
+                        while bytes.get(index).map(|c| *c != b'\n').unwrap_or(false)
+                        {
+                            index += 1;
+                        }
+                    },
+                    Some(c) if c.is_ascii_whitespace() => index += 1,
+                    _ => break
+                }
+            }
+
+            let start = index;
+            while bytes.get(index).map(|c| !c.is_ascii_whitespace()).unwrap_or(false)
+            {
+                index += 1;
+            }
+
+            (start != index).then(|| &bytes[start..index])
+        };
+
+        if next_token() != Some(b"P6")
+        {
+            return Err(invalid("not a binary ppm file"));
+        }
+
+        let mut next_number = ||
+        {
+            next_token()
+                .and_then(|token| std::str::from_utf8(token).ok())
+                .and_then(|token| token.parse::<usize>().ok())
+                .ok_or_else(|| invalid("malformed ppm header"))
+        };
+
+        let width = next_number()?;
+        let height = next_number()?;
+        let max_value = next_number()?;
+
+        if max_value == 0 || max_value > u16::MAX as usize
+        {
+            return Err(invalid("ppm max value out of range"));
+        }
+
+        // exactly one whitespace byte between the header and the data
+        let data = bytes.get(index + 1..).unwrap_or(&[]);
+
+        let channel_size = if max_value > 255
+        {
+            2
+        } else
+        {
+            1
+        };
+
+        let expected = width.checked_mul(height)
+            .and_then(|amount| amount.checked_mul(3 * channel_size))
+            .ok_or_else(|| invalid("ppm dimensions too big"))?;
+
+        if data.len() < expected
+        {
+            return Err(invalid("ppm pixel data is truncated"));
+        }
+
+        let values = data[..expected].chunks(channel_size).map(|value|
+        {
+            let value = value.iter().fold(0, |acc, byte| (acc << 8) | *byte as usize);
+
+            ((value * 255 + max_value / 2) / max_value).min(255) as u8
+        }).collect::<Vec<_>>();
+
+        let mut image = Self::new(width, height, Color::black());
+        image.data.iter_mut().zip(values.chunks(3)).for_each(|(c, values)|
+        {
+            *c = Color{r: values[0], g: values[1], b: values[2]};
+        });
+
+        Ok(image)
+    }
+
+    // png for .png paths, ppm for anything else
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()>
+    {
+        let format = ImageFormat::from_path(&path).unwrap_or(ImageFormat::Ppm);
+
+        self.save_with_format(path, format)
+    }
+
+    pub fn save_with_format(&self, path: impl AsRef<Path>, format: ImageFormat) -> io::Result<()>
+    {
+        match format
+        {
+            ImageFormat::Ppm => self.write_to(&mut File::create(path)?),
+            ImageFormat::Png => self.save_png(path)
+        }
+    }
+
+    pub fn save_png(&self, path: impl AsRef<Path>) -> io::Result<()>
+    {
+        if self.width == 0 || self.height == 0
+        {
+            panic!("cant save a 0 sized image");
+        }
+
+        let rgb = self.data.iter().flat_map(|c| [c.r, c.g, c.b]).collect::<Vec<_>>();
+
+        File::create(path)?.write_all(&png::encode(self.width, self.height, &rgb))
+    }
+
+    // same as save but with 16 bits per channel
+    pub fn save_ppm16(&self, path: impl AsRef<Path>) -> io::Result<()>
+    {
+        self.write_ppm_with(&mut File::create(path)?, u16::MAX, |value, data|
+        {
+            // 255 * 257 is 65535 so the full range gets used
+            data.extend((value as u16 * 257).to_be_bytes())
+        })
+    }
+
+    // writes the same p6 ppm that save does, into anything
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()>
+    {
+        self.write_ppm_with(writer, 255, |value, data| data.push(value))
+    }
+
+    fn write_ppm_with<W, F>(&self, writer: &mut W, max_value: u16, mut push: F) -> io::Result<()>
+    where
+        W: Write,
+        F: FnMut(u8, &mut Vec<u8>)
+    {
+        if self.width == 0 || self.height == 0
+        {
+            panic!("cant save a 0 sized image");
+        }
+
+        let header = format!("P6\n{} {}\n{max_value}\n", self.width, self.height);
+
+        writer.write_all(header.as_bytes())?;
+
+        let mut data = Vec::with_capacity(self.data.len() * 3);
+        self.data.iter().flat_map(|c| [c.r, c.g, c.b]).for_each(|value|
+        {
+            push(value, &mut data);
+        });
+
+        writer.write_all(&data)
+    }
+
+    pub fn text_between(
+        &mut self,
+        font: &Font,
+        color: impl ColorRepr,
+        bb: BoundingBox,
+        align_h: TextHAlign,
+        align_v: TextVAlign,
+        text: &str
+    ) -> BoundingBox
+    {
+        let (text_bb, size) = self.text_between_layout(font, bb, align_h, align_v, text);
+        let size = Point2::repeat(size);
+
+        // the box goes down to the bottom of the descenders, the text starts above them
+        let descent = -self.text_bounds(font, Point2::repeat(0.0), size, text).bottom_left.y;
+
+        self.text(font, color, Point2{y: text_bb.bottom_left.y + descent, ..text_bb.bottom_left}, size, text)
+    }
+
+    // the area text_between would draw into, without drawing anything
+    pub fn text_between_bounds(
+        &mut self,
+        font: &Font,
+        bb: BoundingBox,
+        align_h: TextHAlign,
+        align_v: TextVAlign,
+        text: &str
+    ) -> BoundingBox
+    {
+        self.text_between_layout(font, bb, align_h, align_v, text).0
+    }
+
+    fn text_between_layout(
+        &mut self,
+        font: &Font,
+        bb: BoundingBox,
+        align_h: TextHAlign,
+        align_v: TextVAlign,
+        text: &str
+    ) -> (BoundingBox, f64)
+    {
+        let text_size = self.text_size(font, Point2::repeat(1.0), text);
+
+        let goal_size = bb.top_right - bb.bottom_left;
+
+        let size = goal_size / text_size;
+
+        let scale = if size.x > size.y
+        {
+            Point2{
+                x: size.y / size.x,
+                y: 1.0
+            }
+        } else
+        {
+            Point2{
+                x: 1.0,
+                y: size.x / size.y,
+            }
+        };
+
+        // never bigger than the box, even with rounding or a degenerate text size
+        let fit_inside = |value: f64, limit: f64|
+        {
+            if value.is_nan() { 0.0 } else { value.clamp(0.0, limit.max(0.0)) }
+        };
+
+        let real_size = Point2{
+            x: fit_inside(goal_size.x * scale.x, goal_size.x),
+            y: fit_inside(goal_size.y * scale.y, goal_size.y)
+        };
+
+        // keep aspect ratio
+        let size = size.x.min(size.y);
+        let size = if size.is_finite() { size.max(0.0) } else { 0.0 };
+
+        let pos = match align_h
+        {
+            TextHAlign::Left => bb.bottom_left,
+            TextHAlign::Middle =>
+            {
+                Point2{
+                    x: (bb.bottom_left.x + bb.top_right.x - real_size.x) * 0.5,
+                    y: bb.bottom_left.y
+                }
+            },
+            TextHAlign::Right =>
+            {
+                Point2{
+                    x: bb.top_right.x - real_size.x,
+                    y: bb.bottom_left.y
+                }
+            }
+        };
+
+        let pos = match align_v
+        {
+            TextVAlign::Bottom => pos,
+            TextVAlign::Middle =>
+            {
+                Point2{
+                    x: pos.x,
+                    y: (bb.bottom_left.y + bb.top_right.y - real_size.y) * 0.5
+                }
+            },
+            TextVAlign::Top =>
+            {
+                Point2{
+                    x: pos.x,
+                    y: bb.top_right.y - real_size.y
+                }
+            }
+        };
+
+        let highest_pos = (bb.top_right - real_size).max(bb.bottom_left);
+        let pos = pos.clamp(bb.bottom_left, highest_pos);
+
+        let text_bb = BoundingBox{
+            bottom_left: pos,
+            top_right: pos + real_size
+        };
+
+        (text_bb, size)
+    }
+
+    pub fn text_size(
+        &mut self,
+        font: &Font,
+        size: Point2<f64>,
+        text: &str
+    ) -> Point2<f64>
+    {
+        let bb = self.text_bounds(font, Point2::repeat(0.0), size, text);
+
+        bb.top_right - bb.bottom_left
+    }
+
+    // the box text would take up, without rasterizing anything, position is where
+    // the text sits and descenders go below it
+    fn text_bounds(
+        &mut self,
+        font: &Font,
+        position: Point2<f64>,
+        size: Point2<f64>,
+        text: &str
+    ) -> BoundingBox
+    {
+        let line_height = font.line_height(self.without_aspect(size));
+
+        let right = self.text_char_positions(font, position, size, text).last()
+            .map(|CharInfo{size, position, c, ..}| position.x + (c.width() * size.x))
+            .unwrap_or(position.x);
+
+        let descent = self.text_char_positions(font, position, size, text)
+            .map(|CharInfo{size, c, ..}| c.descent() * size.y)
+            .fold(0.0, f64::max);
+
+        BoundingBox{
+            bottom_left: Point2{x: position.x, y: position.y - descent},
+            top_right: Point2{x: right, y: position.y + line_height}
+        }
+    }
+
+    pub fn text(
+        &mut self,
+        font: &Font,
+        color: impl ColorRepr,
+        position: Point2<f64>,
+        size: Point2<f64>,
+        text: &str
+    ) -> BoundingBox
+    {
+        let bb = self.text_bounds(font, position, size, text);
+
+        let image_size = Point2{x: self.width, y: self.height};
+
+        // quarter pixel steps, so labels of almost the same size share their glyphs
+        let scale = self.width.max(self.height) as f64 * 4.0;
+        let quantize = |value: f64| (value * scale).round() as u32;
+
+        self.text_char_positions(font, position, size, text)
+            .for_each(|CharInfo{character, size, position, thickness, c}|
+            {
+                let size_key = Point2{x: quantize(size.x), y: quantize(size.y)};
+                let thickness_key = quantize(thickness);
+
+                let key = (character, image_size, size_key, thickness_key);
+                let offsets = font.cached_glyph(key, ||
+                {
+                    self.glyph_offsets(c, size_key.cast::<f64>() / scale, thickness_key as f64 / scale)
+                });
+
+                // glyphs always start on a whole pixel so the cached ones line up
+                let origin = self.to_local_f(position);
+                let origin = Point2{x: origin.x.floor() as i32, y: origin.y.floor() as i32};
+
+                offsets.iter().for_each(|offset|
+                {
+                    let pixel = origin + *offset;
+
+                    if pixel.x < 0 || pixel.y < 0
+                    {
+                        return;
+                    }
+
+                    if let Some(p) = self.get_mut(pixel.cast())
+                    {
+                        *p = color.set(*p);
+                    }
+                });
+            });
+
+        bb
+    }
+
+    // pixels of a glyph drawn with its bottom left corner on the top left of the image
+    fn glyph_offsets(&self, c: &FontChar, size: Point2<f64>, thickness: f64) -> Vec<Point2<i32>>
+    {
+        let reach = thickness * self.width.max(self.height) as f64;
+
+        // fonts can draw outside of 0..1, so the margin comes from the lines themselves
+        let (lowest, highest) = c.lines().iter().flat_map(|line| [line.start, line.end])
+            .fold((Point2::repeat(0.0), Point2::repeat(0.0)), |(lowest, highest), point|
+            {
+                (lowest.min(point), highest.max(point))
+            });
+
+        // going left and going up both end up below zero in pixels, keep all of it positive
+        let margin = Point2{
+            x: (-lowest.x * c.width() * size.x * self.width as f64 + reach + 2.0).ceil(),
+            y: (highest.y * size.y * self.height as f64 + reach + 2.0).ceil()
+        };
+
+        let to_local = |point: Point2<f64>|
+        {
+            (self.to_local_f(point) + margin).cast::<usize>()
+        };
+
+        let mut pixels = c.lines().iter().flat_map(|line|
+        {
+            let to_glyph = |mut p: Point2<f64>|
+            {
+                p.x *= c.width();
+
+                Point2{x: 0.0, y: 1.0} + p * size
+            };
+
+            self.line_thick_pixels_with(
+                to_glyph(line.start),
+                to_glyph(line.end),
+                thickness,
+                to_local
+            )
+        }).collect::<Vec<_>>();
+
+        pixels.sort_unstable_by_key(|pixel| (pixel.y, pixel.x));
+        pixels.dedup();
+
+        let margin = margin.cast::<i32>();
+
+        pixels.into_iter().map(|pixel| pixel.cast::<i32>() - margin).collect()
+    }
+
+    fn text_char_positions<'a>(
+        &mut self,
+        font: &'a Font,
+        mut position: Point2<f64>,
+        size: Point2<f64>,
+        text: &'a str
+    ) -> impl Iterator<Item=CharInfo<'a>> + 'a
+    {
+        let thickness = size.x.min(size.y) * 0.05;
 
         let size = self.without_aspect(size);
 
         let mut step_size = 0.0;
-        text.chars().filter_map(|c| font.get(c)).map(move |c|
+        text.chars().filter_map(|character|
+        {
+            font.get_or_fallback(character).map(|c| (character, c))
+        }).map(move |(character, c)|
         {
             // all this weirdness to not add step_size at the last char
             position.x += step_size;
             step_size = c.total_step() * size.x;
 
-            CharInfo{size, position, thickness, c}
+            CharInfo{character, size, position, thickness, c}
         })
     }
 
-    pub fn sdf_drawer(&mut self) -> DeferredSDFDrawer
+    pub fn sdf_drawer(&mut self) -> DeferredSDFDrawer<'_>
     {
         DeferredSDFDrawer{image: self, lines: Vec::new()}
     }
@@ -568,69 +1300,161 @@ impl PPMImage
 
     fn sdf_lines(&mut self, lines: Vec<Line>)
     {
-        let mut i = 0;
-        for y in 0..self.height
+        if self.width == 0
         {
-            for x in 0..self.width
+            return;
+        }
+
+        // lines get bucketed by their bounding box in pixels so each pixel only
+        // checks the lines that can reach it, indices stay sorted to keep the draw order
+        let cell_size = 16;
+        let cells = Point2{
+            x: self.width.div_ceil(cell_size),
+            y: self.height.div_ceil(cell_size)
+        };
+
+        let mut grid: Vec<Vec<usize>> = vec![Vec::new(); cells.x * cells.y];
+        lines.iter().enumerate().for_each(|(index, line)|
+        {
+            let to_pixel = |point: Point2<f64>|
             {
-                let curr = Point2{
-                    x: x as f64 / self.width as f64,
-                    y: 1.0 - (y as f64 / self.height as f64)
-                };
+                let point = self.without_aspect(point);
+
+                Point2{x: point.x * self.width as f64, y: (1.0 - point.y) * self.height as f64}
+            };
+
+            let a = to_pixel(line.p0.min(line.p1) - line.thickness);
+            let b = to_pixel(line.p0.max(line.p1) + line.thickness);
 
-                let curr = self.with_aspect(curr);
+            // an extra pixel of padding for rounding
+            let low = (a.min(b) - 1.0).cast::<usize>() / cell_size;
+            let high = (a.max(b) + 1.0).cast::<usize>() / cell_size;
 
-                for line in lines.iter().copied().rev()
+            for y in low.y.min(cells.y - 1)..=high.y.min(cells.y - 1)
+            {
+                for x in low.x.min(cells.x - 1)..=high.x.min(cells.x - 1)
                 {
-                    let Line{
-                        p0,
-                        p1,
-                        thickness,
-                        c,
-                        half_length,
-                        local_length,
-                        clip_distance,
-                        rotation
-                    } = line;
-
-                    let curr_distance = curr - p0;
-                    let curr_distance = curr_distance.x.powi(2) + curr_distance.y.powi(2);
-
-                    if curr_distance > clip_distance
-                    {
-                        continue;
-                    }
+                    grid[y * cells.x + x].push(index);
+                }
+            }
+        });
 
-                    let mut start_cap = SignedDistance::new(curr);
-                    let mut end_cap = SignedDistance::new(curr);
+        // every pixel only depends on the lines so the rows can be done in any order
+        let mut data = mem::take(&mut self.data);
 
-                    let mut body = SignedDistance::new(curr);
+        let draw_row = |(y, row): (usize, &mut [Color])|
+        {
+            // colors of the lines covering the current pixel, topmost first
+            let mut hits: Vec<ColorAlpha> = Vec::new();
 
-                    start_cap.translate(p0);
-                    end_cap.translate(p1);
+            row.iter_mut().enumerate().for_each(|(x, pixel)|
+            {
+                let bucket = &grid[(y / cell_size) * cells.x + x / cell_size];
 
-                    body.translate(p0);
+                self.sdf_pixel(Point2{x, y}, bucket.iter().map(|index| &lines[*index]), &mut hits, pixel);
+            });
+        };
 
-                    body.rotate(rotation);
+        #[cfg(feature = "rayon")]
+        data.par_chunks_mut(self.width).enumerate().for_each(draw_row);
 
-                    body.translate(Point2{x: half_length, y: 0.0});
-                    body.scale(Point2{x: local_length, y: 1.0});
+        #[cfg(not(feature = "rayon"))]
+        data.chunks_mut(self.width).enumerate().for_each(draw_row);
 
-                    let is_cap = (start_cap.circle(thickness) < 0.0)
-                        || (end_cap.circle(thickness) < 0.0);
+        self.data = data;
+    }
 
-                    let is_body = body.rectangle(thickness) < 0.0;
+    // blends every line in lines that covers the pixel at pos, lines go in draw order
+    fn sdf_pixel<'b>(
+        &self,
+        pos: Point2<usize>,
+        lines: impl DoubleEndedIterator<Item=&'b Line>,
+        hits: &mut Vec<ColorAlpha>,
+        pixel: &mut Color
+    )
+    {
+        let curr = Point2{
+            x: pos.x as f64 / self.width as f64,
+            y: 1.0 - (pos.y as f64 / self.height as f64)
+        };
 
-                    if is_cap || is_body
-                    {
-                        *unsafe{ self.data.get_unchecked_mut(i) } = c;
-                        break;
-                    }
-                }
+        let curr = self.with_aspect(curr);
 
-                i += 1;
+        hits.clear();
+        for line in lines.rev()
+        {
+            let Line{
+                p0,
+                p1,
+                thickness,
+                c,
+                half_length,
+                local_length,
+                clip_distance,
+                rotation
+            } = *line;
+
+            let curr_distance = curr.distance_squared(p0);
+
+            if curr_distance > clip_distance
+            {
+                continue;
+            }
+
+            let mut start_cap = SignedDistance::new(curr);
+            let mut end_cap = SignedDistance::new(curr);
+
+            let mut body = SignedDistance::new(curr);
+
+            start_cap.translate(p0);
+            end_cap.translate(p1);
+
+            body.translate(p0);
+
+            body.rotate(rotation);
+
+            body.translate(Point2{x: half_length, y: 0.0});
+            body.scale(Point2{x: local_length, y: 1.0});
+
+            let is_cap = (start_cap.circle(thickness) < 0.0)
+                || (end_cap.circle(thickness) < 0.0);
+
+            let is_body = body.rectangle(thickness) < 0.0;
+
+            if is_cap || is_body
+            {
+                hits.push(c);
+
+                // nothing below an opaque line can show through
+                if c.a == u8::MAX
+                {
+                    break;
+                }
             }
         }
+
+        if !hits.is_empty()
+        {
+            *pixel = hits.iter().rev().fold(*pixel, |acc, c| c.set(acc));
+        }
+    }
+
+    // checks every line for every pixel, what the grid in sdf_lines has to match
+    #[cfg(test)]
+    fn sdf_lines_brute(&mut self, lines: Vec<Line>)
+    {
+        let mut data = mem::take(&mut self.data);
+        let mut hits = Vec::new();
+
+        data.chunks_mut(self.width.max(1)).enumerate().for_each(|(y, row)|
+        {
+            row.iter_mut().enumerate().for_each(|(x, pixel)|
+            {
+                self.sdf_pixel(Point2{x, y}, lines.iter(), &mut hits, pixel);
+            });
+        });
+
+        self.data = data;
     }
 
     pub fn line_thick(
@@ -641,12 +1465,79 @@ impl PPMImage
         c: impl ColorRepr
     )
     {
-        self.line_thick_pixels(p0, p1, thickness).into_iter().for_each(|pixel|
+        self.line_thick_unique_pixels(p0, p1, thickness).into_iter().for_each(|pixel|
         {
             self[pixel] = c.set(self[pixel]);
         });
     }
 
+    pub fn line_thick_styled(
+        &mut self,
+        p0: Point2<f64>,
+        p1: Point2<f64>,
+        thickness: f64,
+        c: impl ColorRepr,
+        style: LineStyle
+    )
+    {
+        let diff = p1 - p0;
+
+        // walked in the aspect corrected space so the pieces dont stretch with the angle
+        let length = self.with_aspect(diff).magnitude();
+
+        let point_at = |distance: f64| p0 + diff * (distance / length);
+
+        // anything shorter would draw the same pixels over and over
+        let pixel = 1.0 / self.width.min(self.height).max(1) as f64;
+
+        match style
+        {
+            LineStyle::Solid => self.line_thick(p0, p1, thickness, c),
+            LineStyle::Dashed{on, off} =>
+            {
+                // a dot, same as dotted does
+                if length == 0.0
+                {
+                    self.circle(p0, thickness, c);
+
+                    return;
+                }
+
+                let on = on.max(pixel);
+
+                let mut start = 0.0;
+                while start < length
+                {
+                    let end = (start + on).min(length);
+
+                    self.line_thick(point_at(start), point_at(end), thickness, c);
+
+                    start += on + off.max(0.0);
+                }
+            },
+            LineStyle::Dotted{spacing} =>
+            {
+                let spacing = spacing.max(pixel);
+
+                let mut distance = 0.0;
+                while distance <= length
+                {
+                    let point = if length == 0.0
+                    {
+                        p0
+                    } else
+                    {
+                        point_at(distance)
+                    };
+
+                    self.circle(point, thickness, c);
+
+                    distance += spacing;
+                }
+            }
+        }
+    }
+
     pub fn line_thick_pixels(
         &self,
         p0: Point2<f64>,
@@ -654,10 +1545,37 @@ impl PPMImage
         thickness: f64
     ) -> HashSet<Point2<usize>>
     {
-        let mut pixels = HashSet::new();
+        self.line_thick_unique_pixels(p0, p1, thickness).into_iter().collect()
+    }
+
+    // same pixels as line_thick_pixels without hashing them, each one shows up once
+    fn line_thick_unique_pixels(
+        &self,
+        p0: Point2<f64>,
+        p1: Point2<f64>,
+        thickness: f64
+    ) -> Vec<Point2<usize>>
+    {
+        self.line_thick_pixels_with(p0, p1, thickness, |point| self.to_local(point))
+    }
+
+    fn line_thick_pixels_with(
+        &self,
+        p0: Point2<f64>,
+        p1: Point2<f64>,
+        thickness: f64,
+        to_local: impl Fn(Point2<f64>) -> Point2<usize>
+    ) -> Vec<Point2<usize>>
+    {
+        let mut pixels = Vec::new();
+
+        let triangle_pixels = |p0, p1, p2|
+        {
+            Self::triangle_local_pixels(to_local(p0), to_local(p1), to_local(p2))
+        };
 
         let diff = p1 - p0;
-        let angle = -diff.y.atan2(diff.x);
+        let angle = diff.y.atan2(diff.x);
 
         // borrow checker why r u like this
         let direction = |this: &Self, raw: Point2<f64>|
@@ -699,13 +1617,53 @@ impl PPMImage
                 )
             };
 
-            pixels.extend(self.triangle_pixels(p0 - up, p0 + middle_n, p0 + end_n));
-            pixels.extend(self.triangle_pixels(p1 - up, p1 + middle, p1 + end));
+            pixels.extend(triangle_pixels(p0 - up, p0 + middle_n, p0 + end_n));
+            pixels.extend(triangle_pixels(p1 - up, p1 + middle, p1 + end));
         }
 
         // the line
-        pixels.extend(self.triangle_pixels(p0 + up, p1 + up, p0 - up));
-        pixels.extend(self.triangle_pixels(p0 - up, p1 + up, p1 - up));
+        pixels.extend(triangle_pixels(p0 + up, p1 + up, p0 - up));
+        pixels.extend(triangle_pixels(p0 - up, p1 + up, p1 - up));
+
+        Self::unique_pixels(pixels)
+    }
+
+    // the triangles overlap, a bitmap over their bounding box gets rid of the repeats
+    // without hashing anything, long diagonal lines have mostly empty boxes so those get sorted
+    fn unique_pixels(mut pixels: Vec<Point2<usize>>) -> Vec<Point2<usize>>
+    {
+        let first = if let Some(first) = pixels.first()
+        {
+            *first
+        } else
+        {
+            return pixels;
+        };
+
+        let (low, high) = pixels.iter().fold((first, first), |(low, high), pixel|
+        {
+            (
+                Point2{x: low.x.min(pixel.x), y: low.y.min(pixel.y)},
+                Point2{x: high.x.max(pixel.x), y: high.y.max(pixel.y)}
+            )
+        });
+
+        let size = high - low + 1;
+        if size.x * size.y > pixels.len() * 4
+        {
+            pixels.sort_unstable_by_key(|pixel| (pixel.y, pixel.x));
+            pixels.dedup();
+
+            return pixels;
+        }
+
+        let mut visited = vec![false; size.x * size.y];
+        pixels.retain(|pixel|
+        {
+            let local = *pixel - low;
+
+            !mem::replace(&mut visited[local.y * size.x + local.x], true)
+        });
 
         pixels
     }
@@ -725,6 +1683,114 @@ impl PPMImage
         }
     }
 
+    // edges share their corner pixels so each pixel only gets colored once
+    pub fn rect_outline(&mut self, bb: BoundingBox, thickness: f64, c: impl ColorRepr)
+    {
+        let BoundingBox{bottom_left, top_right} = bb;
+
+        let top_left = Point2{x: bottom_left.x, y: top_right.y};
+        let bottom_right = Point2{x: top_right.x, y: bottom_left.y};
+
+        let mut pixels = self.line_thick_pixels(bottom_left, top_left, thickness);
+
+        // a flat box is just a line
+        if bottom_left.x != top_right.x && bottom_left.y != top_right.y
+        {
+            pixels.extend(self.line_thick_unique_pixels(top_left, top_right, thickness));
+            pixels.extend(self.line_thick_unique_pixels(top_right, bottom_right, thickness));
+        }
+
+        pixels.extend(self.line_thick_unique_pixels(bottom_right, bottom_left, thickness));
+
+        pixels.into_iter().for_each(|pixel|
+        {
+            self[pixel] = c.set(self[pixel]);
+        });
+    }
+
+    pub fn bezier_quadratic(
+        &mut self,
+        p0: Point2<f64>,
+        p1: Point2<f64>,
+        p2: Point2<f64>,
+        thickness: f64,
+        c: impl ColorRepr
+    )
+    {
+        let points = self.bezier_points(&[p0, p1, p2], |t|
+        {
+            let it = 1.0 - t;
+
+            p0 * (it * it) + p1 * (2.0 * it * t) + p2 * (t * t)
+        });
+
+        self.polyline_thick(&points, thickness, c);
+    }
+
+    pub fn bezier_cubic(
+        &mut self,
+        p0: Point2<f64>,
+        p1: Point2<f64>,
+        p2: Point2<f64>,
+        p3: Point2<f64>,
+        thickness: f64,
+        c: impl ColorRepr
+    )
+    {
+        let points = self.bezier_cubic_points(p0, p1, p2, p3);
+
+        self.polyline_thick(&points, thickness, c);
+    }
+
+    pub fn bezier_cubic_points(
+        &self,
+        p0: Point2<f64>,
+        p1: Point2<f64>,
+        p2: Point2<f64>,
+        p3: Point2<f64>
+    ) -> Vec<Point2<f64>>
+    {
+        self.bezier_points(&[p0, p1, p2, p3], |t|
+        {
+            let it = 1.0 - t;
+
+            p0 * (it * it * it) + p1 * (3.0 * it * it * t) + p2 * (3.0 * it * t * t) + p3 * (t * t * t)
+        })
+    }
+
+    // the curve never leaves its control polygon so that length is enough to pick the detail
+    fn bezier_points(
+        &self,
+        control: &[Point2<f64>],
+        curve: impl Fn(f64) -> Point2<f64>
+    ) -> Vec<Point2<f64>>
+    {
+        let pixels_per_segment = 4.0;
+
+        let length: f64 = control.iter().zip(control.iter().skip(1)).map(|(a, b)|
+        {
+            self.to_local_f(*a).distance(self.to_local_f(*b))
+        }).sum();
+
+        let segments = ((length / pixels_per_segment).ceil() as usize).clamp(1, 512);
+
+        (0..=segments).map(|i| curve(i as f64 / segments as f64)).collect()
+    }
+
+    // every pixel gets set once so the joints dont get blended twice
+    pub fn polyline_thick(&mut self, points: &[Point2<f64>], thickness: f64, c: impl ColorRepr)
+    {
+        let pixels = points.iter().zip(points.iter().skip(1)).flat_map(|(a, b)|
+        {
+            self.line_thick_unique_pixels(*a, *b, thickness)
+        }).collect::<HashSet<Point2<usize>>>();
+
+        pixels.into_iter().for_each(|pixel|
+        {
+            self[pixel] = c.set(self[pixel]);
+        });
+    }
+
     pub fn circle(&mut self, pos: Point2<f64>, size: f64, c: impl ColorRepr)
     {
         let lod = 9;
@@ -762,6 +1828,68 @@ impl PPMImage
         self.triangle_local(self.to_local(p0), self.to_local(p1), self.to_local(p2), c);
     }
 
+    // same as triangle but the edge pixels get blended by how much of them is covered
+    pub fn triangle_aa(
+        &mut self,
+        p0: Point2<f64>,
+        p1: Point2<f64>,
+        p2: Point2<f64>,
+        c: impl Into<ColorAlpha>
+    )
+    {
+        let c = c.into();
+
+        let points = [self.to_local_f(p0), self.to_local_f(p1), self.to_local_f(p2)];
+
+        let area = (points[1] - points[0]).cross(points[2] - points[0]);
+        if area == 0.0 || !area.is_finite()
+        {
+            return;
+        }
+
+        // distances to each edge, positive on the inside no matter the winding
+        let edges = [(points[0], points[1]), (points[1], points[2]), (points[2], points[0])]
+            .map(|(a, b)|
+            {
+                let edge = b - a;
+
+                (a, edge * (area.signum() / edge.magnitude()))
+            });
+
+        let low = points[0].min(points[1]).min(points[2]);
+        let high = points[0].max(points[1]).max(points[2]);
+
+        let start = (low - 1.0).max(Point2::repeat(0.0)).cast::<usize>();
+        let end = Point2{
+            x: (high.x + 1.0).min(self.width as f64),
+            y: (high.y + 1.0).min(self.height as f64)
+        }.cast::<usize>();
+
+        for y in start.y..end.y
+        {
+            for x in start.x..end.x
+            {
+                let center = Point2{x: x as f64 + 0.5, y: y as f64 + 0.5};
+
+                let distance = edges.iter().map(|(a, edge)|
+                {
+                    edge.cross(center - *a)
+                }).fold(f64::INFINITY, f64::min);
+
+                let coverage = (distance + 0.5).clamp(0.0, 1.0);
+                if coverage == 0.0
+                {
+                    continue;
+                }
+
+                let a = (c.a as f64 * coverage).round() as u8;
+
+                let pixel = Point2{x, y};
+                self[pixel] = ColorAlpha{a, ..c}.set(self[pixel]);
+            }
+        }
+    }
+
     fn triangle_local(
         &mut self,
         p0: Point2<usize>,
@@ -786,6 +1914,60 @@ impl PPMImage
         Self::triangle_local_pixels(self.to_local(p0), self.to_local(p1), self.to_local(p2))
     }
 
+    // even-odd fill so self intersecting polygons get holes where they overlap
+    pub fn polygon(&mut self, points: &[Point2<f64>], c: impl ColorRepr)
+    {
+        self.polygon_pixels(points).into_iter().for_each(|pixel|
+        {
+            self[pixel] = c.set(self[pixel]);
+        });
+    }
+
+    pub fn polygon_pixels(&self, points: &[Point2<f64>]) -> Vec<Point2<usize>>
+    {
+        if points.len() < 3 || self.width == 0 || self.height == 0
+        {
+            return Vec::new();
+        }
+
+        let points = points.iter().map(|point| self.to_local_f(*point)).collect::<Vec<_>>();
+
+        let y_lowest = points.iter().map(|point| point.y).fold(f64::INFINITY, f64::min);
+        let y_highest = points.iter().map(|point| point.y).fold(f64::NEG_INFINITY, f64::max);
+
+        let y_start = y_lowest.max(0.0) as usize;
+        let y_end = (y_highest.ceil().max(0.0) as usize).min(self.height);
+
+        let mut result = Vec::new();
+        let mut crossings = Vec::new();
+        for y in y_start..y_end
+        {
+            // sample through the middle of the pixel row
+            let sample_y = y as f64 + 0.5;
+
+            crossings.clear();
+            let edges = points.iter().zip(points.iter().cycle().skip(1));
+
+            // half open so a vertex on the row only counts once
+            crossings.extend(edges.filter(|(a, b)| (a.y <= sample_y) != (b.y <= sample_y)).map(|(a, b)|
+            {
+                a.x + (sample_y - a.y) / (b.y - a.y) * (b.x - a.x)
+            }));
+
+            crossings.sort_by(|a, b| a.total_cmp(b));
+
+            for pair in crossings.chunks_exact(2)
+            {
+                let start = (pair[0] - 0.5).ceil().max(0.0) as usize;
+                let end = ((pair[1] - 0.5).ceil().max(0.0) as usize).min(self.width);
+
+                result.extend((start..end).map(|x| Point2{x, y}));
+            }
+        }
+
+        result
+    }
+
     pub fn triangle_local_pixels(
         p0: Point2<usize>,
         p1: Point2<usize>,
@@ -988,8 +2170,8 @@ impl PPMImage
         let p = self.to_local_f(point);
 
         Point2{
-            x: (p.x as usize).max(0).min(self.width - 1),
-            y: (p.y as usize).max(0).min(self.height - 1)
+            x: (p.x as usize).min(self.width - 1),
+            y: (p.y as usize).min(self.height - 1)
         }
     }
 
@@ -1006,6 +2188,25 @@ impl PPMImage
         self.index_maybe(pos).expect("index out of range")
     }
 
+    pub fn get(&self, pos: Point2<usize>) -> Option<Color>
+    {
+        self.index_maybe(pos).map(|index| self.data[index])
+    }
+
+    // returns false if the position is outside the image
+    pub fn set(&mut self, pos: Point2<usize>, c: Color) -> bool
+    {
+        if let Some(pixel) = self.get_mut(pos)
+        {
+            *pixel = c;
+
+            true
+        } else
+        {
+            false
+        }
+    }
+
     fn get_mut(&mut self, pos: Point2<usize>) -> Option<&mut Color>
     {
         self.index_maybe(pos).map(|index|
@@ -1033,3 +2234,598 @@ impl IndexMut<Point2<usize>> for PPMImage
         &mut self.data[index]
     }
 }
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use std::{env, fs};
+
+
+    // lib and bin tests run at the same time, so the pid keeps their files apart
+    fn temp_path(name: &str) -> std::path::PathBuf
+    {
+        env::temp_dir().join(format!("graphthingy_{}_{name}", std::process::id()))
+    }
+
+    // the same lines every time, all over the place with different widths and alphas
+    fn sdf_scene_lines(image: &mut PPMImage, amount: usize) -> Vec<Line>
+    {
+        let mut drawer = image.sdf_drawer();
+        (0..amount).for_each(|index|
+        {
+            let value = |offset: usize|
+            {
+                // splitmix64
+                let hashed = ((index * 5 + offset) as u64).wrapping_mul(0x9e3779b97f4a7c15);
+                let hashed = (hashed ^ (hashed >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                let hashed = (hashed ^ (hashed >> 27)).wrapping_mul(0x94d049bb133111eb);
+
+                ((hashed ^ (hashed >> 31)) >> 11) as f64 / (1_u64 << 53) as f64
+            };
+
+            let p0 = Point2{x: value(0), y: value(1)};
+            let p1 = Point2{x: value(2), y: value(3)};
+
+            let c = ColorAlpha{
+                r: (index * 37) as u8,
+                g: (index * 91) as u8,
+                b: (index * 53) as u8,
+                a: [255, 120, 40][index % 3]
+            };
+
+            drawer.line(p0, p1, 0.002 + value(4) * 0.02, c);
+        });
+
+        drawer.lines
+    }
+
+    fn sdf_scene(width: usize, height: usize, amount: usize) -> PPMImage
+    {
+        let mut image = PPMImage::new(width, height, Color::white());
+
+        let lines = sdf_scene_lines(&mut image, amount);
+        image.sdf_lines(lines);
+
+        image
+    }
+
+    #[test]
+    fn ppm16_header_and_size()
+    {
+        let mut image = PPMImage::new(3, 2, Color::white());
+        image[Point2{x: 1, y: 0}] = Color{r: 1, g: 128, b: 0};
+
+        let path8 = temp_path("eight.ppm");
+        let path16 = temp_path("sixteen.ppm");
+
+        image.save(&path8).unwrap();
+        image.save_ppm16(&path16).unwrap();
+
+        let bytes8 = fs::read(&path8).unwrap();
+        let bytes16 = fs::read(&path16).unwrap();
+
+        fs::remove_file(path8).unwrap();
+        fs::remove_file(path16).unwrap();
+
+        let header8 = b"P6\n3 2\n255\n";
+        let header16 = b"P6\n3 2\n65535\n";
+
+        assert!(bytes8.starts_with(header8));
+        assert!(bytes16.starts_with(header16));
+
+        let data8 = &bytes8[header8.len()..];
+        let data16 = &bytes16[header16.len()..];
+
+        assert_eq!(data8.len(), 3 * 2 * 3);
+        assert_eq!(data16.len(), 3 * 2 * 6);
+
+        // big endian and stretched so 255 turns into 65535
+        assert_eq!(&data16[..6], &[255, 255, 255, 255, 255, 255]);
+        assert_eq!(&data16[6..12], &[1, 1, 128, 128, 0, 0]);
+    }
+
+    #[test]
+    fn gray_values()
+    {
+        let red = Color{r: 255, g: 0, b: 0};
+
+        // 0.299 * 255 rounds to 76
+        assert_eq!(red.to_gray(), Color{r: 76, g: 76, b: 76});
+        assert_eq!(Color{r: 0, g: 255, b: 0}.to_gray(), Color{r: 150, g: 150, b: 150});
+        assert_eq!(Color::white().to_gray(), Color::white());
+        assert_eq!(Color::black().to_gray(), Color::black());
+
+        let mut image = PPMImage::new(2, 1, red);
+        image.grayscale();
+
+        assert_eq!(image[Point2{x: 1, y: 0}], red.to_gray());
+    }
+
+    #[test]
+    fn mix_averages()
+    {
+        let red = Color{r: 255, g: 0, b: 0};
+        let blue = Color{r: 0, g: 0, b: 255};
+
+        assert_eq!(Color::mix(&[red, blue]), Color{r: 127, g: 0, b: 127});
+        assert_eq!(Color::mix(&[red]), red);
+        assert_eq!(Color::mix(&[]), Color::black());
+    }
+
+    #[test]
+    fn named_colors_round_trip()
+    {
+        assert!(Color::all_named().any(|(name, color)| name == "red" && color == Color{r: 255, g: 0, b: 0}));
+
+        Color::all_named().for_each(|(name, color)|
+        {
+            assert_eq!(Color::from_name(name), Some(color), "{name}");
+        });
+
+        assert_eq!(Color::from_name("not a color"), None);
+    }
+
+
+    #[test]
+    fn clip_line_cases()
+    {
+        let bb = BoundingBox{bottom_left: Point2{x: 0.0, y: 0.0}, top_right: Point2{x: 10.0, y: 10.0}};
+
+        let inside = (Point2{x: 1.0, y: 2.0}, Point2{x: 8.0, y: 9.0});
+        assert_eq!(clip_line(inside.0, inside.1, bb), Some(inside));
+
+        assert_eq!(clip_line(Point2{x: -5.0, y: 1.0}, Point2{x: -1.0, y: 9.0}, bb), None);
+        assert_eq!(clip_line(Point2{x: 11.0, y: 5.0}, Point2{x: 20.0, y: 5.0}, bb), None);
+
+        // passes by the corner without touching the box
+        assert_eq!(clip_line(Point2{x: -1.0, y: 9.0}, Point2{x: 9.0, y: 21.0}, bb), None);
+
+        assert_eq!(
+            clip_line(Point2{x: -5.0, y: 5.0}, Point2{x: 5.0, y: 5.0}, bb),
+            Some((Point2{x: 0.0, y: 5.0}, Point2{x: 5.0, y: 5.0}))
+        );
+
+        assert_eq!(
+            clip_line(Point2{x: -5.0, y: -5.0}, Point2{x: 15.0, y: 15.0}, bb),
+            Some((Point2{x: 0.0, y: 0.0}, Point2{x: 10.0, y: 10.0}))
+        );
+
+        assert_eq!(
+            clip_line(Point2{x: 5.0, y: 20.0}, Point2{x: 5.0, y: -20.0}, bb),
+            Some((Point2{x: 5.0, y: 10.0}, Point2{x: 5.0, y: 0.0}))
+        );
+    }
+
+
+    #[test]
+    fn ppm_load_round_trip()
+    {
+        let mut image = PPMImage::new(4, 3, Color::black());
+        image.data.iter_mut().enumerate().for_each(|(index, c)|
+        {
+            let value = (index * 23) as u8;
+            *c = Color{r: value, g: 255 - value, b: value.wrapping_mul(7)};
+        });
+
+        let path8 = temp_path("load_eight.ppm");
+        let path16 = temp_path("load_sixteen.ppm");
+
+        image.save(&path8).unwrap();
+        image.save_ppm16(&path16).unwrap();
+
+        let loaded8 = PPMImage::load(&path8).unwrap();
+        let loaded16 = PPMImage::load(&path16).unwrap();
+
+        fs::remove_file(path8).unwrap();
+        fs::remove_file(path16).unwrap();
+
+        [loaded8, loaded16].into_iter().for_each(|loaded|
+        {
+            assert_eq!((loaded.width(), loaded.height()), (4, 3));
+            assert_eq!(loaded.data, image.data);
+        });
+
+        // comments in the header get skipped
+        let loaded = PPMImage::from_ppm_bytes(b"P6\n# a comment\n1 1\n255\n\x01\x02\x03").unwrap();
+        assert_eq!(loaded.data, [Color{r: 1, g: 2, b: 3}]);
+
+        assert!(PPMImage::from_ppm_bytes(b"P6\n2 2\n255\n\x01\x02\x03").is_err());
+        assert!(PPMImage::from_ppm_bytes(b"P3\n1 1\n255\n1 2 3").is_err());
+    }
+
+
+    #[test]
+    fn triangle_aa_blends_edges()
+    {
+        let mut image = PPMImage::new(20, 20, Color::black());
+
+        // the long edge goes right through the centers of the pixels where x + y is 19
+        image.triangle_aa(
+            Point2{x: 0.1, y: 0.1},
+            Point2{x: 0.1, y: 0.9},
+            Point2{x: 0.9, y: 0.9},
+            Color::white()
+        );
+
+        assert_eq!(image[Point2{x: 4, y: 4}], Color::white());
+        assert_eq!(image[Point2{x: 15, y: 15}], Color::black());
+
+        let edge = image[Point2{x: 10, y: 9}];
+        assert!((100..156).contains(&edge.r), "edge pixel was {edge:?}");
+
+        let partial = image.data.iter().filter(|c| **c != Color::white() && **c != Color::black()).count();
+        assert!(partial >= 16, "only {partial} blended pixels");
+    }
+
+
+    #[test]
+    fn polygon_pixels_even_odd()
+    {
+        let image = PPMImage::new(10, 10, Color::white());
+
+        // written in pixel coordinates with y going down, like the pixels that come out
+        let pixels = |points: &[(f64, f64)]|
+        {
+            let points = points.iter().map(|(x, y)| Point2{x: x / 10.0, y: 1.0 - y / 10.0}).collect::<Vec<_>>();
+
+            let mut pixels = image.polygon_pixels(&points);
+            pixels.sort_by_key(|pos| (pos.y, pos.x));
+
+            pixels
+        };
+
+        let square = pixels(&[(2.0, 4.0), (6.0, 4.0), (6.0, 8.0), (2.0, 8.0)]);
+        let expected = (4..8).flat_map(|y| (2..6).map(move |x| Point2{x, y})).collect::<Vec<_>>();
+        assert_eq!(square, expected);
+
+        // an arrow pointing right with a notch cut into its back, 50 - 20 in area
+        let arrow = pixels(&[(0.0, 0.0), (10.0, 5.0), (0.0, 10.0), (4.0, 5.0)]);
+        assert_eq!(arrow.len(), 30);
+        assert!(arrow.contains(&Point2{x: 8, y: 4}));
+        assert!(!arrow.contains(&Point2{x: 1, y: 5}));
+
+        let row = |y| arrow.iter().filter(|pos| pos.y == y).map(|pos| pos.x).collect::<Vec<_>>();
+        assert_eq!(row(2), [2, 3, 4]);
+        assert_eq!(row(5), [4, 5, 6, 7, 8]);
+
+        // the middle of a star is inside twice, so even odd leaves a hole there
+        let star = pixels(&[(0.0, 3.5), (10.0, 3.5), (2.0, 10.0), (5.0, 0.0), (8.0, 10.0)]);
+        assert_eq!(star.len(), 30);
+        assert!([(4, 2), (0, 3), (9, 3), (2, 9), (7, 9)].into_iter().all(|(x, y)| star.contains(&Point2{x, y})));
+        assert!((4..=5).all(|x| (4..=6).all(|y| !star.contains(&Point2{x, y}))));
+    }
+
+
+    #[test]
+    fn dashed_and_dotted_lines()
+    {
+        let row = |style|
+        {
+            let mut image = PPMImage::new(100, 20, Color::white());
+            image.line_thick_styled(Point2{x: 0.1, y: 0.5}, Point2{x: 0.9, y: 0.5}, 0.02, Color::black(), style);
+
+            image
+        };
+
+        let is_set = |image: &PPMImage, x| (0..20).any(|y| image[Point2{x, y}] == Color::black());
+
+        let solid = row(LineStyle::Solid);
+        assert!((15..85).all(|x| is_set(&solid, x)));
+
+        // the width is 5 units long here, so 10 pixels on and 10 off
+        let dashed = row(LineStyle::Dashed{on: 0.5, off: 0.5});
+        let gaps = (15..85).filter(|x| !is_set(&dashed, *x)).count();
+        assert!((20..50).contains(&gaps), "{gaps} unset pixels along the dashed line");
+
+        // way under a pixel still ends up as one pixel pieces instead of looping forever
+        let tiny = row(LineStyle::Dashed{on: 1e-12, off: 0.1});
+        assert!((15..85).any(|x| is_set(&tiny, x)));
+
+        let tiny = row(LineStyle::Dotted{spacing: 0.0});
+        assert!((15..85).all(|x| is_set(&tiny, x)));
+
+        let dot = |style|
+        {
+            let mut image = PPMImage::new(20, 20, Color::white());
+            image.line_thick_styled(Point2::repeat(0.5), Point2::repeat(0.5), 0.1, Color::black(), style);
+
+            image.data
+        };
+
+        let dotted = dot(LineStyle::Dotted{spacing: 0.2});
+        assert!(dotted.contains(&Color::black()));
+        assert_eq!(dot(LineStyle::Dashed{on: 0.2, off: 0.2}), dotted);
+    }
+
+
+    #[test]
+    fn blur_spreads_evenly()
+    {
+        let mut image = PPMImage::new(11, 11, Color::black());
+
+        let center = Point2{x: 5, y: 5};
+        image[center] = Color::white();
+
+        image.blur(1.5);
+
+        let value = |x: usize, y: usize| image[Point2{x, y}].r;
+
+        assert!(value(5, 5) > 0 && value(5, 5) < 255);
+        assert!(image.data.iter().all(|c| c.r <= value(5, 5) && c.r == c.g && c.g == c.b));
+
+        (0..11).for_each(|y|
+        {
+            (0..11).for_each(|x|
+            {
+                let c = value(x, y);
+
+                assert_eq!(c, value(10 - x, y));
+                assert_eq!(c, value(x, 10 - y));
+                assert_eq!(c, value(y, x));
+            });
+        });
+
+        // gets darker going out from the middle
+        assert!((5..10).all(|x| value(x, 5) >= value(x + 1, 5)));
+        assert!(value(5, 5) > value(6, 5) && value(6, 5) > value(8, 5));
+    }
+
+
+    #[test]
+    fn color_equality_and_debug()
+    {
+        let c = Color{r: 10, g: 20, b: 30};
+
+        assert_eq!(c, Color{r: 10, g: 20, b: 30});
+        assert_ne!(c, Color{r: 10, g: 20, b: 31});
+        assert_eq!(format!("{c:?}"), "Color { r: 10, g: 20, b: 30 }");
+
+        let alpha = ColorAlpha::from(c);
+
+        assert_eq!(alpha, ColorAlpha{r: 10, g: 20, b: 30, a: 255});
+        assert_ne!(alpha, ColorAlpha{a: 254, ..alpha});
+        assert_eq!(format!("{alpha:?}"), "ColorAlpha { r: 10, g: 20, b: 30, a: 255 }");
+
+        assert_eq!(ColorAlpha::from(Color::white()), ColorAlpha::white());
+    }
+
+
+    #[test]
+    fn crop_known_pattern()
+    {
+        // every pixel remembers where it came from
+        let mut image = PPMImage::new(6, 4, Color::black());
+        (0..4).for_each(|y| (0..6).for_each(|x| image[Point2{x, y}] = Color{r: x as u8, g: y as u8, b: 0}));
+
+        let crop = |bottom_left: (usize, usize), top_right: (usize, usize)|
+        {
+            let bb = BoundingBox{
+                bottom_left: Point2{x: bottom_left.0, y: bottom_left.1},
+                top_right: Point2{x: top_right.0, y: top_right.1}
+            };
+
+            image.crop(bb)
+        };
+
+        let cropped = crop((2, 1), (5, 3));
+        assert_eq!((cropped.width(), cropped.height()), (3, 2));
+        assert_eq!(cropped[Point2{x: 0, y: 0}], Color{r: 2, g: 1, b: 0});
+        assert_eq!(cropped[Point2{x: 2, y: 1}], Color{r: 4, g: 2, b: 0});
+
+        // past the edges gets clamped to the image
+        let cropped = crop((4, 2), (100, 100));
+        assert_eq!((cropped.width(), cropped.height()), (2, 2));
+        assert_eq!(cropped[Point2{x: 1, y: 1}], Color{r: 5, g: 3, b: 0});
+
+        let cropped = crop((10, 10), (20, 20));
+        assert_eq!((cropped.width(), cropped.height()), (0, 0));
+
+        // backwards boxes end up empty instead of underflowing
+        let cropped = crop((3, 3), (1, 1));
+        assert_eq!((cropped.width(), cropped.height()), (0, 0));
+    }
+
+
+    #[test]
+    fn resize_checkerboard()
+    {
+        let mut image = PPMImage::new(2, 2, Color::black());
+        image[Point2{x: 1, y: 0}] = Color::white();
+        image[Point2{x: 0, y: 1}] = Color::white();
+
+        let bilinear = image.resize(Point2{x: 8, y: 8}, ResizeMode::Bilinear);
+        assert_eq!((bilinear.width(), bilinear.height()), (8, 8));
+
+        // the corners stay as they were, the middle is halfway
+        assert_eq!(bilinear[Point2{x: 0, y: 0}], Color::black());
+        assert_eq!(bilinear[Point2{x: 7, y: 0}], Color::white());
+
+        let middle = bilinear[Point2{x: 4, y: 4}];
+        assert!((100..156).contains(&middle.r), "middle was {middle:?}");
+
+        let grays = bilinear.data.iter().filter(|c| **c != Color::white() && **c != Color::black()).count();
+        assert!(grays > 30, "only {grays} in between pixels");
+
+        let nearest = image.resize(Point2{x: 8, y: 8}, ResizeMode::Nearest);
+        assert!(nearest.data.iter().all(|c| *c == Color::white() || *c == Color::black()));
+        assert_eq!(nearest[Point2{x: 3, y: 3}], Color::black());
+        assert_eq!(nearest[Point2{x: 4, y: 3}], Color::white());
+
+        [ResizeMode::Nearest, ResizeMode::Bilinear].into_iter().for_each(|mode|
+        {
+            let empty = image.resize(Point2{x: 0, y: 5}, mode);
+            assert_eq!((empty.width(), empty.height()), (0, 5));
+
+            let empty = image.resize(Point2{x: 5, y: 0}, mode);
+            assert_eq!((empty.width(), empty.height()), (5, 0));
+        });
+    }
+
+
+    #[test]
+    fn sdf_lines_match_serial_render()
+    {
+        let image = sdf_scene(160, 120, 100);
+
+        // fnv 1a, cant change between rust versions like the std hasher can
+        let hash = image.data.iter().flat_map(|c| [c.r, c.g, c.b]).fold(0xcbf29ce484222325_u64, |hash, byte|
+        {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+
+        // rendered once without the rayon feature, with it the rows must come out the same
+        assert_eq!(hash, 0x491cf911f30d6099);
+    }
+
+
+    #[test]
+    fn flood_fill_stays_inside_border()
+    {
+        let red = Color{r: 255, g: 0, b: 0};
+
+        // a black frame from 2 to 7 with white inside and outside of it
+        let mut image = PPMImage::new(10, 10, Color::white());
+        (2..=7).for_each(|i|
+        {
+            [Point2{x: i, y: 2}, Point2{x: i, y: 7}, Point2{x: 2, y: i}, Point2{x: 7, y: i}]
+                .into_iter()
+                .for_each(|pos| image[pos] = Color::black());
+        });
+
+        image.flood_fill(Point2{x: 4, y: 5}, red);
+
+        (0..10).for_each(|y|
+        {
+            (0..10).for_each(|x|
+            {
+                let pos = Point2{x, y};
+
+                let on_frame = (2..=7).contains(&x) && (2..=7).contains(&y)
+                    && (x == 2 || x == 7 || y == 2 || y == 7);
+
+                let inside = (3..7).contains(&x) && (3..7).contains(&y);
+
+                let expected = if on_frame
+                {
+                    Color::black()
+                } else if inside
+                {
+                    red
+                } else
+                {
+                    Color::white()
+                };
+
+                assert_eq!(image[pos], expected, "at {pos:?}");
+            });
+        });
+
+        // starting outside the image does nothing
+        let before = image.data.clone();
+        image.flood_fill(Point2{x: 10, y: 0}, red);
+        assert_eq!(image.data, before);
+    }
+
+
+    #[test]
+    fn sdf_grid_matches_brute_force()
+    {
+        // wide and tall, neither a multiple of the 16 pixel cells
+        [(160, 120), (37, 130)].into_iter().for_each(|(width, height)|
+        {
+            let mut grid = PPMImage::new(width, height, Color::white());
+
+            let mut lines = sdf_scene_lines(&mut grid, 30);
+
+            // thin ones going across the cell edges
+            let mut drawer = grid.sdf_drawer();
+            drawer.line(Point2{x: 0.05, y: 0.5}, Point2{x: 0.95, y: 0.52}, 0.003, Color::black());
+
+            let edge = 16.0 / width as f64;
+            drawer.line(Point2{x: edge - 0.01, y: 0.1}, Point2{x: edge + 0.01, y: 0.9}, 0.004, Color::black());
+            lines.extend(drawer.lines);
+
+            let mut brute = PPMImage::new(width, height, Color::white());
+            brute.sdf_lines_brute(lines.clone());
+
+            grid.sdf_lines(lines);
+
+            assert!(brute.data.iter().any(|c| *c != Color::white()));
+            assert!(grid.data == brute.data, "{width}x{height} differs from the brute force render");
+        });
+    }
+
+
+    #[test]
+    fn text_is_deterministic()
+    {
+        let font = Font::default();
+
+        let render = |font: &Font|
+        {
+            let mut image = PPMImage::new(200, 60, Color::white());
+            image.text(font, Color::black(), Point2{x: 0.05, y: 0.3}, Point2::repeat(0.2), "aabb 0.5%");
+
+            image.data
+        };
+
+        // the second one gets its glyphs from the cache the first one filled
+        let first = render(&font);
+        assert!(first.contains(&Color::black()));
+
+        assert!(render(&font) == first);
+        assert!(render(&Font::default()) == first);
+    }
+
+    #[test]
+    fn text_near_edges()
+    {
+        let font = Font::default();
+
+        let mut image = PPMImage::new(80, 40, Color::white());
+
+        [(0.95, 0.0), (-0.1, 0.95), (0.0, -0.05), (0.9, 0.9), (1.2, 0.5)].into_iter().for_each(|(x, y)|
+        {
+            image.text(&font, Color::black(), Point2{x, y}, Point2::repeat(0.3), "Wg-9");
+        });
+
+        assert_eq!((image.width(), image.height()), (80, 40));
+        assert_eq!(image.data.len(), 80 * 40);
+
+        // fitting into a box on the edge keeps the text in that box
+        let bb = BoundingBox{bottom_left: Point2{x: 0.8, y: 0.85}, top_right: Point2{x: 1.0, y: 1.0}};
+        let text_bb = image.text_between(&font, Color::black(), bb, TextHAlign::Right, TextVAlign::Top, "1234");
+
+        assert!(text_bb.bottom_left.x >= bb.bottom_left.x - 1e-9 && text_bb.top_right.x <= 1.0 + 1e-9);
+        assert!(text_bb.bottom_left.y >= bb.bottom_left.y - 1e-9 && text_bb.top_right.y <= 1.0 + 1e-9);
+    }
+
+
+    #[test]
+    fn descenders_stay_in_the_box()
+    {
+        let font = Font::default();
+
+        let mut image = PPMImage::new(200, 100, Color::white());
+
+        let size = image.text_size(&font, Point2::repeat(0.2), "abc");
+        assert!(size.x > 0.0 && size.y > 0.0);
+
+        // a g reaches below the baseline so the box has to be taller
+        assert!(image.text_size(&font, Point2::repeat(0.2), "gy").y > size.y);
+
+        let bb = BoundingBox{bottom_left: Point2{x: 0.2, y: 0.3}, top_right: Point2{x: 0.8, y: 0.7}};
+        image.text_between(&font, Color::black(), bb, TextHAlign::Middle, TextVAlign::Middle, "jpgqy");
+
+        // rows 30 to 70 in pixels, with a pixel or two for the line thickness
+        let outside = (0..100).filter(|y| !(28..73).contains(y)).any(|y|
+        {
+            (0..200).any(|x| image[Point2{x, y}] != Color::white())
+        });
+
+        assert!(image.data.contains(&Color::black()));
+        assert!(!outside, "text went outside of its box");
+    }
+}
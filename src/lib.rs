@@ -1,21 +1,42 @@
-pub use graph::{GrapherConfig, Grapher};
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "std")]
+pub use graph::{GrapherConfig, GrapherConfigBuilder, ConfigError, ParseError, Grapher, Grid, InputFormat, PaletteKind, RunningAvgWindow, SeriesSelector, StepMode, series_color};
+
+#[cfg(feature = "std")]
 pub use image::{
     PPMImage,
+    ImageFormat,
+    ResizeMode,
     DeferredSDFDrawer,
     Color,
     ColorRepr,
     ColorAlpha,
     BoundingBox,
+    LineStyle,
     TextHAlign,
-    TextVAlign
+    TextVAlign,
+    clip_line
 };
 
 pub use point::Point2;
-pub use font::{Font, FontChar};
+
+#[cfg(feature = "std")]
+pub use font::{Font, FontChar, FontParseError};
 
 pub mod point;
+
+pub mod stats;
+mod math;
+
+#[cfg(feature = "std")]
 pub mod font;
 
+#[cfg(feature = "std")]
 pub mod graph;
+
+#[cfg(feature = "std")]
 mod image;
+
+#[cfg(feature = "std")]
+mod png;
@@ -1,33 +1,67 @@
-use std::env;
+use std::{env, fmt, process};
 
-use graph::{GrapherConfig, Grapher};
+use graph::{GrapherConfig, Grapher, PaletteKind};
 use config::Config;
 
 pub use image::{
     PPMImage,
+    ImageFormat,
+    ResizeMode,
     DeferredSDFDrawer,
     Color,
     ColorRepr,
     ColorAlpha,
     BoundingBox,
+    LineStyle,
     TextHAlign,
-    TextVAlign
+    TextVAlign,
+    clip_line
 };
 
 pub use point::Point2;
-pub use font::{Font, FontChar};
+pub use font::{Font, FontChar, FontParseError};
 
 mod point;
+mod stats;
+mod math;
 mod font;
 
 mod graph;
 mod image;
+mod png;
 mod config;
 
 
+// bad options are the users fault, a message reads better than a panic
+fn or_exit<T, E: fmt::Display>(result: Result<T, E>) -> T
+{
+    result.unwrap_or_else(|err|
+    {
+        eprintln!("{err}");
+
+        process::exit(1)
+    })
+}
+
 fn main()
 {
-    let config = Config::parse(env::args().skip(1)).unwrap();
+    let config = or_exit(Config::parse(env::args().skip(1)));
+
+    if config.list_colors
+    {
+        println!("palettes:");
+        PaletteKind::all().for_each(|palette| println!("    {}", palette.name()));
+
+        println!("named colors:");
+        Color::all_named().for_each(|(name, Color{r, g, b})|
+        {
+            println!("    {name} ({r}, {g}, {b})");
+        });
+
+        return;
+    }
+
+    let default_config = GrapherConfig::default();
 
     let grapher_config = GrapherConfig{
         log_scale: config.log_scale,
@@ -36,15 +70,54 @@ fn main()
         max_height: config.max_height,
         running_avg: config.running_avg,
         plot_line: config.plot_line,
-        ..Default::default()
+        grayscale: config.grayscale,
+        palette_kind: config.palette_kind,
+        shadow: config.shadow,
+        x_range: config.x_range,
+        sdf_lines: config.sdf_lines,
+        invert_y: config.invert_y,
+        guide_alpha: config.guide_alpha.unwrap_or(default_config.guide_alpha),
+        mark_extremes: config.mark_extremes,
+        y_labels: config.y_labels.unwrap_or(default_config.y_labels),
+        mark_plateaus: config.mark_plateaus,
+        color_by_name: config.color_by_name,
+        fit_range: config.fit_range,
+        y_precision: config.y_precision.unwrap_or(default_config.y_precision),
+        marker_stride: config.marker_stride.unwrap_or(default_config.marker_stride),
+        y_ticks: config.y_ticks,
+        equal_aspect: config.equal_aspect,
+        crosshair: config.crosshair.map(|(x, y)| Point2{x, y}),
+        show_points: !config.hide_points,
+        grid_on_top: config.grid_on_top,
+        last_value: config.last_value,
+        strict_palette: config.strict_palette,
+        weight_markers: config.weight_markers,
+        fill_area: config.fill_area,
+        step: config.step,
+        smooth: config.smooth,
+        select: config.select,
+        x_label: config.x_label,
+        y_label: config.y_label,
+        label_indices: config.label_indices,
+        percent_difference: config.percent_difference,
+        ..default_config
     };
 
-    let mut grapher = Grapher::new(grapher_config);
+    let mut grapher = or_exit(Grapher::new(grapher_config));
 
     for data in config.paths
     {
-        grapher.parse(data).unwrap();
+        grapher.parse_format(data, config.input_format).unwrap();
+    }
+
+    if config.describe
+    {
+        println!("{:#?}", grapher.describe());
+
+        return;
     }
 
-    grapher.save(Point2{x: 4000, y: 2000}, "graph.ppm").unwrap();
+    let image = or_exit(grapher.to_image_checked(Point2{x: 4000, y: 2000}));
+
+    image.save("graph.ppm").unwrap();
 }
@@ -0,0 +1,45 @@
+// float functions that live in std, with libm filling in for no_std builds
+
+#[cfg(not(any(feature = "std", feature = "libm")))]
+compile_error!("either the std or the libm feature has to be enabled");
+
+macro_rules! float_fn
+{
+    ($name:ident, $libm_name:ident, ($($arg:ident),*)) =>
+    {
+        #[cfg(feature = "std")]
+        pub fn $name(x: f64, $($arg: f64),*) -> f64
+        {
+            x.$name($($arg),*)
+        }
+
+        #[cfg(not(feature = "std"))]
+        pub fn $name(x: f64, $($arg: f64),*) -> f64
+        {
+            libm::$libm_name(x, $($arg),*)
+        }
+    }
+}
+
+float_fn!{sqrt, sqrt, ()}
+float_fn!{sin, sin, ()}
+float_fn!{cos, cos, ()}
+float_fn!{atan, atan, ()}
+float_fn!{powf, pow, (n)}
+float_fn!{hypot, hypot, (y)}
+float_fn!{floor, floor, ()}
+float_fn!{log10, log10, ()}
+float_fn!{ln, log, ()}
+float_fn!{exp, exp, ()}
+
+#[cfg(feature = "std")]
+pub fn powi(x: f64, n: i32) -> f64
+{
+    x.powi(n)
+}
+
+#[cfg(not(feature = "std"))]
+pub fn powi(x: f64, n: i32) -> f64
+{
+    libm::pow(x, n as f64)
+}
@@ -0,0 +1,467 @@
+// just enough png to write 8 bit rgb images, compressed with fixed huffman deflate
+
+
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+    35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258
+];
+
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+    3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0
+];
+
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+    257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577
+];
+
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+    7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13
+];
+
+const WINDOW_SIZE: usize = 1 << 15;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+
+// how many older positions with the same hash get checked
+const MAX_CHAIN: usize = 32;
+
+pub fn encode(width: usize, height: usize, rgb: &[u8]) -> Vec<u8>
+{
+    assert_eq!(rgb.len(), width * height * 3);
+
+    let mut raw = Vec::with_capacity((width * 3 + 1) * height);
+    rgb.chunks(width * 3).for_each(|row|
+    {
+        // no filter
+        raw.push(0);
+        raw.extend_from_slice(row);
+    });
+
+    let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+    let mut header = Vec::with_capacity(13);
+    header.extend((width as u32).to_be_bytes());
+    header.extend((height as u32).to_be_bytes());
+
+    // 8 bit depth, truecolor, deflate, default filtering, no interlacing
+    header.extend([8, 2, 0, 0, 0]);
+
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib(&raw));
+    write_chunk(&mut png, b"IEND", &[]);
+
+    png
+}
+
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8])
+{
+    png.extend((data.len() as u32).to_be_bytes());
+
+    let start = png.len();
+
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+
+    let crc = crc32(&png[start..]);
+    png.extend(crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32
+{
+    let table: Vec<u32> = (0..256).map(|n|
+    {
+        (0..8).fold(n as u32, |c, _|
+        {
+            if c & 1 == 1
+            {
+                0xedb88320 ^ (c >> 1)
+            } else
+            {
+                c >> 1
+            }
+        })
+    }).collect();
+
+    !data.iter().fold(u32::MAX, |crc, byte|
+    {
+        table[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+fn adler32(data: &[u8]) -> u32
+{
+    let modulo = 65521;
+
+    let (a, b) = data.iter().fold((1_u32, 0_u32), |(a, b), byte|
+    {
+        let a = (a + *byte as u32) % modulo;
+
+        (a, (b + a) % modulo)
+    });
+
+    (b << 16) | a
+}
+
+fn zlib(data: &[u8]) -> Vec<u8>
+{
+    // deflate with a 32k window, no preset dictionary
+    let mut out = vec![0x78, 0x01];
+
+    out.extend(deflate(data));
+    out.extend(adler32(data).to_be_bytes());
+
+    out
+}
+
+struct BitWriter
+{
+    bytes: Vec<u8>,
+    buffer: u64,
+    amount: u32
+}
+
+impl BitWriter
+{
+    fn new() -> Self
+    {
+        Self{bytes: Vec::new(), buffer: 0, amount: 0}
+    }
+
+    // deflate packs everything starting from the lowest bit
+    fn write(&mut self, value: u32, bits: u32)
+    {
+        self.buffer |= (value as u64) << self.amount;
+        self.amount += bits;
+
+        while self.amount >= 8
+        {
+            self.bytes.push(self.buffer as u8);
+
+            self.buffer >>= 8;
+            self.amount -= 8;
+        }
+    }
+
+    // huffman codes go in starting from the highest bit
+    fn write_code(&mut self, code: u32, bits: u32)
+    {
+        let reversed = code.reverse_bits() >> (32 - bits);
+
+        self.write(reversed, bits);
+    }
+
+    fn finish(mut self) -> Vec<u8>
+    {
+        if self.amount > 0
+        {
+            self.bytes.push(self.buffer as u8);
+        }
+
+        self.bytes
+    }
+}
+
+fn write_literal(writer: &mut BitWriter, value: u16)
+{
+    match value
+    {
+        0..=143 => writer.write_code(0x30 + value as u32, 8),
+        144..=255 => writer.write_code(0x190 + (value as u32 - 144), 9),
+        256..=279 => writer.write_code(value as u32 - 256, 7),
+        _ => writer.write_code(0xc0 + (value as u32 - 280), 8)
+    }
+}
+
+fn write_match(writer: &mut BitWriter, length: usize, distance: usize)
+{
+    let length_index = LENGTH_BASES.iter().rposition(|base| *base as usize <= length)
+        .expect("match length is at least 3");
+
+    write_literal(writer, 257 + length_index as u16);
+    writer.write(
+        (length - LENGTH_BASES[length_index] as usize) as u32,
+        LENGTH_EXTRA[length_index] as u32
+    );
+
+    let distance_index = DISTANCE_BASES.iter().rposition(|base| *base as usize <= distance)
+        .expect("match distance is at least 1");
+
+    writer.write_code(distance_index as u32, 5);
+    writer.write(
+        (distance - DISTANCE_BASES[distance_index] as usize) as u32,
+        DISTANCE_EXTRA[distance_index] as u32
+    );
+}
+
+fn deflate(data: &[u8]) -> Vec<u8>
+{
+    let mut writer = BitWriter::new();
+
+    // a single final block with the fixed huffman codes
+    writer.write(1, 1);
+    writer.write(1, 2);
+
+    let hash_at = |index: usize|
+    {
+        let value = (data[index] as usize) << 16
+            | (data[index + 1] as usize) << 8
+            | data[index + 2] as usize;
+
+        (value.wrapping_mul(2654435761) >> 8) & (WINDOW_SIZE - 1)
+    };
+
+    // most recent position for every hash, and the position before it with the same hash
+    let mut heads = vec![usize::MAX; WINDOW_SIZE];
+    let mut previous = vec![usize::MAX; data.len()];
+
+    let insert = |index: usize, heads: &mut [usize], previous: &mut [usize]|
+    {
+        if index + MIN_MATCH <= data.len()
+        {
+            let hash = hash_at(index);
+
+            previous[index] = heads[hash];
+            heads[hash] = index;
+        }
+    };
+
+    let mut index = 0;
+    while index < data.len()
+    {
+        let mut best = (0, 0);
+
+        if index + MIN_MATCH <= data.len()
+        {
+            let max_length = (data.len() - index).min(MAX_MATCH);
+
+            let mut candidate = heads[hash_at(index)];
+            for _ in 0..MAX_CHAIN
+            {
+                if candidate == usize::MAX || index - candidate > WINDOW_SIZE
+                {
+                    break;
+                }
+
+                let length = (0..max_length)
+                    .take_while(|offset| data[candidate + offset] == data[index + offset])
+                    .count();
+
+                if length > best.0
+                {
+                    best = (length, index - candidate);
+
+                    if length == max_length
+                    {
+                        break;
+                    }
+                }
+
+                candidate = previous[candidate];
+            }
+        }
+
+        let (length, distance) = best;
+        if length >= MIN_MATCH
+        {
+            write_match(&mut writer, length, distance);
+
+            (index..index + length).for_each(|index| insert(index, &mut heads, &mut previous));
+            index += length;
+        } else
+        {
+            write_literal(&mut writer, data[index] as u16);
+
+            insert(index, &mut heads, &mut previous);
+            index += 1;
+        }
+    }
+
+    // end of block
+    write_literal(&mut writer, 256);
+
+    writer.finish()
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    // splits a png into its chunks, checking every crc on the way
+    fn chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)>
+    {
+        let mut chunks = Vec::new();
+
+        let mut rest = &png[8..];
+        while !rest.is_empty()
+        {
+            let length = u32::from_be_bytes(rest[..4].try_into().unwrap()) as usize;
+
+            let kind: [u8; 4] = rest[4..8].try_into().unwrap();
+            let data = rest[8..8 + length].to_vec();
+
+            let crc = u32::from_be_bytes(rest[8 + length..12 + length].try_into().unwrap());
+            assert_eq!(crc, crc32(&rest[4..8 + length]));
+
+            chunks.push((kind, data));
+
+            rest = &rest[12 + length..];
+        }
+
+        chunks
+    }
+
+    struct BitReader<'a>
+    {
+        bytes: &'a [u8],
+        position: usize
+    }
+
+    impl BitReader<'_>
+    {
+        fn bit(&mut self) -> u32
+        {
+            let bit = (self.bytes[self.position / 8] >> (self.position % 8)) & 1;
+            self.position += 1;
+
+            bit as u32
+        }
+
+        fn read(&mut self, bits: u32) -> u32
+        {
+            (0..bits).fold(0, |value, index| value | (self.bit() << index))
+        }
+
+        fn read_code(&mut self, bits: u32) -> u32
+        {
+            (0..bits).fold(0, |code, _| (code << 1) | self.bit())
+        }
+
+        fn literal(&mut self) -> u16
+        {
+            let code = self.read_code(7);
+            if code <= 0b0010111
+            {
+                return 256 + code as u16;
+            }
+
+            let code = (code << 1) | self.bit();
+            match code
+            {
+                0x30..=0xbf => (code - 0x30) as u16,
+                0xc0..=0xc7 => (280 + code - 0xc0) as u16,
+                _ => (144 + ((code << 1) | self.bit()) - 0x190) as u16
+            }
+        }
+    }
+
+    // only understands the single fixed huffman block that deflate writes
+    fn inflate(bytes: &[u8]) -> Vec<u8>
+    {
+        let mut reader = BitReader{bytes, position: 0};
+
+        assert_eq!(reader.read(1), 1);
+        assert_eq!(reader.read(2), 1);
+
+        let mut out: Vec<u8> = Vec::new();
+        loop
+        {
+            let value = reader.literal();
+            match value
+            {
+                0..=255 => out.push(value as u8),
+                256 => return out,
+                _ =>
+                {
+                    let index = (value - 257) as usize;
+                    let length = LENGTH_BASES[index] as usize
+                        + reader.read(LENGTH_EXTRA[index] as u32) as usize;
+
+                    let index = reader.read_code(5) as usize;
+                    let distance = DISTANCE_BASES[index] as usize
+                        + reader.read(DISTANCE_EXTRA[index] as u32) as usize;
+
+                    // byte by byte so matches can overlap what they copy
+                    let start = out.len() - distance;
+                    (0..length).for_each(|offset| out.push(out[start + offset]));
+                }
+            }
+        }
+    }
+
+    fn decode_idat(png: &[u8]) -> Vec<u8>
+    {
+        let data = chunks(png).into_iter().filter(|(kind, _)| kind == b"IDAT")
+            .flat_map(|(_, data)| data)
+            .collect::<Vec<_>>();
+
+        assert_eq!(data[..2], [0x78, 0x01]);
+
+        let (compressed, adler) = data[2..].split_at(data.len() - 6);
+        let raw = inflate(compressed);
+
+        assert_eq!(u32::from_be_bytes(adler.try_into().unwrap()), adler32(&raw));
+
+        raw
+    }
+
+    #[test]
+    fn known_checksums()
+    {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11e60398);
+
+        // a red and a blue pixel, each row starts with a filter byte
+        assert_eq!(adler32(&[0, 255, 0, 0, 0, 0, 255]), 0x070001ff);
+    }
+
+    #[test]
+    fn header_and_end()
+    {
+        let png = encode(2, 1, &[255, 0, 0, 0, 0, 255]);
+
+        assert_eq!(png[..8], [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n']);
+
+        let chunks = chunks(&png);
+        assert_eq!(chunks.iter().map(|(kind, _)| kind).collect::<Vec<_>>(), [b"IHDR", b"IDAT", b"IEND"]);
+
+        let header = &chunks[0].1;
+        assert_eq!(header[..4], 2_u32.to_be_bytes());
+        assert_eq!(header[4..8], 1_u32.to_be_bytes());
+        assert_eq!(header[8..], [8, 2, 0, 0, 0]);
+
+        assert_eq!(png[29..33], 0x7b40e8dd_u32.to_be_bytes());
+        assert_eq!(png[png.len() - 12..], [0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]);
+
+        assert_eq!(decode_idat(&png), [0, 255, 0, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn repetitive_image_round_trips()
+    {
+        // one color over and over makes matches at distance 3 that are way longer than 3
+        let (width, height) = (300, 40);
+        let rgb = (0..width * height).flat_map(|index|
+        {
+            if (index / 7) % 5 == 0
+            {
+                [10, 200, 30]
+            } else
+            {
+                [10, 20, 30]
+            }
+        }).collect::<Vec<u8>>();
+
+        let png = encode(width, height, &rgb);
+
+        let raw = rgb.chunks(width * 3).flat_map(|row| [0].into_iter().chain(row.iter().copied()))
+            .collect::<Vec<u8>>();
+
+        assert!(png.len() < raw.len() / 10);
+        assert_eq!(decode_idat(&png), raw);
+    }
+}
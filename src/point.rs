@@ -1,5 +1,4 @@
-use std::{
-    fmt::Debug,
+use core::{
     ops::{
         Add,
         Sub,
@@ -13,6 +12,43 @@ use std::{
     }
 };
 
+use crate::math;
+
+
+pub trait CastAs<T>
+{
+    fn cast_as(self) -> T;
+}
+
+macro_rules! cast_as_impl_to
+{
+    ($from:ty => $($to:ty),*) =>
+    {
+        $(
+            impl CastAs<$to> for $from
+            {
+                fn cast_as(self) -> $to
+                {
+                    self as $to
+                }
+            }
+        )*
+    }
+}
+
+macro_rules! cast_as_impl
+{
+    ($($from:ty),*) =>
+    {
+        $(
+            cast_as_impl_to!{
+                $from => u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64
+            }
+        )*
+    }
+}
+
+cast_as_impl!{u8, u16, u32, u64, usize, i8, i16, i32, i64, isize, f32, f64}
 
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
 pub struct Point2<T>
@@ -35,11 +71,31 @@ impl<T> Point2<T>
         Self{y: x.clone(), x}
     }
 
-    pub fn cast<U: TryFrom<T>>(self) -> Point2<U>
+    // same rules as an `as` cast: floats to ints truncate towards zero and saturate
+    // (nan turns into 0), ints to smaller ints wrap around
+    // this used to be a checked cast that panicked, now a negative value turns into 0
+    // (or wraps for ints) without failing, so check the sign first if it matters
+    pub fn cast<U>(self) -> Point2<U>
     where
-        <U as TryFrom<T>>::Error: Debug
+        T: CastAs<U>
     {
-        Point2{x: self.x.try_into().unwrap(), y: self.y.try_into().unwrap()}
+        Point2{x: self.x.cast_as(), y: self.y.cast_as()}
+    }
+}
+
+impl<T> From<(T, T)> for Point2<T>
+{
+    fn from((x, y): (T, T)) -> Self
+    {
+        Self{x, y}
+    }
+}
+
+impl<T> From<Point2<T>> for (T, T)
+{
+    fn from(Point2{x, y}: Point2<T>) -> Self
+    {
+        (x, y)
     }
 }
 
@@ -56,16 +112,22 @@ impl Point2<i32>
 
 impl Point2<f64>
 {
-    pub fn rotate(self, rotation: f64) -> Self
+    // counter clockwise with y going up, same direction as atan2 measures
+    pub fn rotate(self, angle: f64) -> Self
     {
-        let (r_sin, r_cos) = rotation.sin_cos();
+        let (r_sin, r_cos) = (math::sin(angle), math::cos(angle));
 
         Point2{
-            x: r_cos * self.x + r_sin * self.y,
-            y: r_cos * self.y - r_sin * self.x
+            x: self.x * r_cos - self.y * r_sin,
+            y: self.x * r_sin + self.y * r_cos
         }
     }
 
+    pub fn rotate_around(self, pivot: Self, angle: f64) -> Self
+    {
+        (self - pivot).rotate(angle) + pivot
+    }
+
     pub fn abs(self) -> Self
     {
         Self{
@@ -73,6 +135,79 @@ impl Point2<f64>
             y: self.y.abs()
         }
     }
+
+    // t isnt clamped so this can extrapolate too
+    pub fn lerp(self, other: Self, t: f64) -> Self
+    {
+        self * (1.0 - t) + other * t
+    }
+
+    pub fn magnitude(self) -> f64
+    {
+        math::hypot(self.x, self.y)
+    }
+
+    pub fn magnitude_squared(self) -> f64
+    {
+        self.dot(self)
+    }
+
+    // the zero vector stays zero instead of turning into nans
+    pub fn normalize(self) -> Self
+    {
+        let magnitude = self.magnitude();
+
+        if magnitude == 0.0
+        {
+            self
+        } else
+        {
+            self / magnitude
+        }
+    }
+
+    pub fn distance(self, other: Self) -> f64
+    {
+        (self - other).magnitude()
+    }
+
+    pub fn distance_squared(self, other: Self) -> f64
+    {
+        (self - other).magnitude_squared()
+    }
+
+    pub fn min(self, other: Self) -> Self
+    {
+        Self{
+            x: self.x.min(other.x),
+            y: self.y.min(other.y)
+        }
+    }
+
+    pub fn max(self, other: Self) -> Self
+    {
+        Self{
+            x: self.x.max(other.x),
+            y: self.y.max(other.y)
+        }
+    }
+
+    // doesnt panic like f64::clamp, if lo is above hi then hi wins
+    pub fn clamp(self, lo: Self, hi: Self) -> Self
+    {
+        self.max(lo).min(hi)
+    }
+
+    pub fn dot(self, other: Self) -> f64
+    {
+        self.x * other.x + self.y * other.y
+    }
+
+    // positive if other is counter clockwise from self
+    pub fn cross(self, other: Self) -> f64
+    {
+        self.x * other.y - self.y * other.x
+    }
 }
 
 macro_rules! op_impl
@@ -181,3 +316,57 @@ op_impl_scalar!{Add, add}
 op_impl_scalar!{Sub, sub}
 op_impl_scalar!{Mul, mul}
 op_impl_scalar!{Div, div}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    use core::f64::consts::{FRAC_PI_2, PI};
+
+
+    fn close(a: Point2<f64>, b: Point2<f64>) -> bool
+    {
+        (a - b).magnitude() < 1e-12
+    }
+
+    #[test]
+    fn rotate_counter_clockwise()
+    {
+        let right = Point2{x: 1.0, y: 0.0};
+
+        assert!(close(right.rotate(FRAC_PI_2), Point2{x: 0.0, y: 1.0}));
+        assert!(close(right.rotate(PI), Point2{x: -1.0, y: 0.0}));
+        assert!(close(right.rotate(-FRAC_PI_2), Point2{x: 0.0, y: -1.0}));
+        assert!(close(Point2{x: 0.0, y: 1.0}.rotate(FRAC_PI_2), Point2{x: -1.0, y: 0.0}));
+
+        let pivot = Point2{x: 2.0, y: 3.0};
+
+        assert!(close(Point2{x: 3.0, y: 3.0}.rotate_around(pivot, FRAC_PI_2), Point2{x: 2.0, y: 4.0}));
+        assert!(close(Point2{x: 3.0, y: 3.0}.rotate_around(pivot, PI), Point2{x: 1.0, y: 3.0}));
+        assert!(close(pivot.rotate_around(pivot, 1.0), pivot));
+    }
+
+
+    #[test]
+    fn cast_saturates_and_wraps()
+    {
+        assert_eq!(Point2{x: -3.7_f64, y: 3.7}.cast::<usize>(), Point2{x: 0, y: 3});
+        assert_eq!(Point2{x: -3.7_f64, y: 3.7}.cast::<i32>(), Point2{x: -3, y: 3});
+        assert_eq!(Point2{x: f64::NAN, y: f64::INFINITY}.cast::<u8>(), Point2{x: 0, y: 255});
+        assert_eq!(Point2{x: 1e10_f64, y: -1e10}.cast::<i32>(), Point2{x: i32::MAX, y: i32::MIN});
+
+        assert_eq!(Point2{x: -1_i32, y: 300}.cast::<u8>(), Point2{x: 255, y: 44});
+        assert_eq!(Point2{x: -1_i32, y: 5}.cast::<usize>(), Point2{x: usize::MAX, y: 5});
+        assert_eq!(Point2{x: usize::MAX, y: 7}.cast::<f64>(), Point2{x: usize::MAX as f64, y: 7.0});
+    }
+
+
+    #[test]
+    fn repeat_any_type()
+    {
+        assert_eq!(Point2::<usize>::repeat(4), Point2{x: 4, y: 4});
+        assert_eq!(Point2::repeat(0.5_f64), Point2{x: 0.5, y: 0.5});
+        assert_eq!(Point2::repeat([1_u8, 2]), Point2{x: [1, 2], y: [1, 2]});
+    }
+}
@@ -0,0 +1,531 @@
+use core::f64;
+
+use crate::{math, Point2};
+
+
+pub struct Line
+{
+    pub slope: f64,
+    pub intercept: f64
+}
+
+impl Line
+{
+    pub fn at_x(&self, x: f64) -> f64
+    {
+        self.slope * x + self.intercept
+    }
+
+    #[allow(dead_code)]
+    pub fn at_y(&self, y: f64) -> f64
+    {
+        (y - self.intercept) / self.slope
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct PearsonCorrCoeff
+{
+    pub r: f64,
+    pub p: f64
+}
+
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy)]
+pub struct Prediction
+{
+    pub mean: f64,
+    // 95% prediction interval for a new value at that x
+    pub lower: f64,
+    pub upper: f64
+}
+
+
+// approximating the beta function was a mistake lmao
+#[allow(dead_code)]
+pub fn integrate_exclude_start<F>(start: f64, end: f64, steps: u32, f: F) -> f64
+where
+    F: Fn(f64) -> f64
+{
+    assert!(steps > 2);
+
+    let steps = steps * 2;
+
+    let area = end - start;
+
+    let step = (steps as f64).recip();
+
+    // uses simpson's rule
+
+    let steps = steps + 1;
+    let value_at = |n|
+    {
+        let n = n as f64 / steps as f64;
+
+        start + n * area
+    };
+
+    let s: f64 = (1..=steps).map(|n|
+    {
+        let value = f(value_at(n));
+
+        let n = n - 1;
+        let factor = if (n == 0) || (n == steps - 1)
+        {
+            1.0
+        } else if n % 2 == 1
+        {
+            4.0
+        } else
+        {
+            2.0
+        };
+
+        let result = value * factor;
+
+        result * step
+    }).sum();
+
+    s / 3.0
+}
+
+// the correction series of stirlings approximation
+fn stirling_terms(z: f64) -> f64
+{
+    1.0 + [
+        (1.0, 12.0),
+        (1.0, 288.0),
+        (-139.0, 51840.0),
+        (-571.0, 2488320.0),
+        (163879.0, 209018880.0),
+        (524819.0, 75246796800.0),
+        (-534703531.0, 902961561600.0)
+    ].into_iter().enumerate().map(|(index, (top, bottom))|
+    {
+        let p = index + 1;
+
+        top / (bottom * math::powi(z, p as i32))
+    }).sum::<f64>()
+}
+
+fn gamma_big(z: f64) -> f64
+{
+    math::sqrt(2.0 * f64::consts::PI / z) * math::powf(z / f64::consts::E, z) * stirling_terms(z)
+}
+
+// stirling is accurate enough from here on, smaller values get shifted up to it
+const GAMMA_SHIFT_LIMIT: f64 = 20.0;
+
+/// ```
+/// let value = graphthingy::stats::gamma(5.0);
+///
+/// assert!((value - 24.0).abs() < 1e-9);
+/// ```
+pub fn gamma(z: f64) -> f64
+{
+    if !z.is_finite() || (z <= 0.0 && math::floor(z) == z)
+    {
+        return f64::NAN;
+    }
+
+    // reflection formula, so negative values dont need a shift per unit
+    if z < 0.5
+    {
+        return f64::consts::PI / (math::sin(f64::consts::PI * z) * gamma(1.0 - z));
+    }
+
+    // gamma(z) = gamma(z + n) / (z * (z + 1) * ... * (z + n - 1))
+    let mut z = z;
+    let mut product = 1.0;
+    while z < GAMMA_SHIFT_LIMIT
+    {
+        product *= z;
+        z += 1.0;
+    }
+
+    gamma_big(z) / product
+}
+
+// natural log of gamma for positive z, stays finite long after gamma itself overflows
+fn ln_gamma(z: f64) -> f64
+{
+    if !z.is_finite() || z <= 0.0
+    {
+        return f64::NAN;
+    }
+
+    let mut z = z;
+    let mut shift = 0.0;
+    while z < GAMMA_SHIFT_LIMIT
+    {
+        shift += math::ln(z);
+        z += 1.0;
+    }
+
+    let ln_big = 0.5 * math::ln(2.0 * f64::consts::PI / z)
+        + z * (math::ln(z) - 1.0)
+        + math::ln(stirling_terms(z));
+
+    ln_big - shift
+}
+
+// gamma((df + 1) / 2) / gamma(df / 2), in log space so big df doesnt turn into inf / inf
+fn students_t_gamma_ratio(df: f64) -> f64
+{
+    math::exp(ln_gamma((df + 1.0) / 2.0) - ln_gamma(df / 2.0))
+}
+
+// not using this, its implemented with gamma anyway
+#[allow(dead_code)]
+pub fn beta(x: f64, y: f64) -> f64
+{
+    /*integrate_exclude_start(0.0, 1.0, 25000, |t|
+    {
+        let left = math::powf(t, x - 1.0);
+        let right = math::powf(1.0 - t, y - 1.0);
+
+        left * right
+    })*/
+    
+    let top = gamma(x) * gamma(y);
+    let bottom = gamma(x + y);
+
+    top / bottom
+}
+
+// simpsons rule over an even amount of intervals
+fn simpson<F>(start: f64, end: f64, intervals: u32, f: F) -> f64
+where
+    F: Fn(f64) -> f64
+{
+    let intervals = intervals.max(2) + intervals % 2;
+
+    let step = (end - start) / intervals as f64;
+
+    let inner: f64 = (1..intervals).map(|n|
+    {
+        let factor = if n % 2 == 1 { 4.0 } else { 2.0 };
+
+        factor * f(start + n as f64 * step)
+    }).sum();
+
+    (f(start) + inner + f(end)) * step / 3.0
+}
+
+pub fn students_t_pdf(df: f64, t: f64) -> f64
+{
+    let vh = (df + 1.0) / 2.0;
+
+    let scale = students_t_gamma_ratio(df) / math::sqrt(f64::consts::PI * df);
+
+    let right = math::powf(1.0 + (math::powi(t, 2) / df), vh).recip();
+
+    scale * right
+}
+
+// probability of a value at most t
+#[allow(dead_code)]
+pub fn students_t_cdf(df: f64, t: f64) -> f64
+{
+    if t.is_nan()
+    {
+        return f64::NAN;
+    }
+
+    // substituting t = sqrt(df) * tan(theta) keeps the range finite and the integrand smooth
+    let theta = math::atan(t.abs() / math::sqrt(df));
+
+    let scale = students_t_gamma_ratio(df) / math::sqrt(f64::consts::PI);
+
+    let half_area = simpson(0.0, theta, 1000, |theta|
+    {
+        scale * math::powf(math::cos(theta), df - 1.0)
+    });
+
+    let half_area = if half_area.is_finite() { half_area.min(0.5) } else { 0.5 };
+
+    if t < 0.0
+    {
+        0.5 - half_area
+    } else
+    {
+        0.5 + half_area
+    }
+}
+
+// the t where the cdf reaches p, found by bisection
+#[allow(dead_code)]
+pub fn students_t_quantile(df: f64, p: f64) -> f64
+{
+    if !(0.0..=1.0).contains(&p)
+    {
+        return f64::NAN;
+    }
+
+    let (mut low, mut high) = (-1.0, 1.0);
+
+    while students_t_cdf(df, low) > p && low > -1e12
+    {
+        low *= 2.0;
+    }
+
+    while students_t_cdf(df, high) < p && high < 1e12
+    {
+        high *= 2.0;
+    }
+
+    for _ in 0..100
+    {
+        let middle = (low + high) / 2.0;
+
+        if students_t_cdf(df, middle) < p
+        {
+            low = middle;
+        } else
+        {
+            high = middle;
+        }
+    }
+
+    (low + high) / 2.0
+}
+
+// 1, 2 or 5 times a power of 10, rounding to the closest one or with round off
+// to the smallest one thats at least as big, the sign is kept
+#[allow(dead_code)]
+pub fn nice_number(value: f64, round: bool) -> f64
+{
+    if value == 0.0 || !value.is_finite()
+    {
+        return value;
+    }
+
+    let magnitude = math::powi(10.0, math::floor(math::log10(value.abs())) as i32);
+    let fraction = value.abs() / magnitude;
+
+    let nice = if round
+    {
+        if fraction < 1.5
+        {
+            1.0
+        } else if fraction < 3.0
+        {
+            2.0
+        } else if fraction < 7.0
+        {
+            5.0
+        } else
+        {
+            10.0
+        }
+    } else if fraction <= 1.0
+    {
+        1.0
+    } else if fraction <= 2.0
+    {
+        2.0
+    } else if fraction <= 5.0
+    {
+        5.0
+    } else
+    {
+        10.0
+    };
+
+    nice * magnitude * value.signum()
+}
+
+fn mean_of(values: impl Iterator<Item=f64>, len: usize) -> f64
+{
+    values.sum::<f64>() / len as f64
+}
+
+pub fn mean(values: impl ExactSizeIterator<Item=f64>) -> f64
+{
+    let len = values.len();
+
+    mean_of(values, len)
+}
+
+pub fn sample_standard_deviation(values: impl ExactSizeIterator<Item=f64> + Clone) -> f64
+{
+    let mean = mean(values.clone());
+
+    let len = values.len();
+    let variance = mean_of(values.map(|x| math::powi(x - mean, 2)), len - 1);
+
+    math::sqrt(variance)
+}
+
+fn standard_scores(
+    values: impl ExactSizeIterator<Item=f64> + Clone
+) -> impl ExactSizeIterator<Item=f64> + Clone
+{
+    let mean = mean(values.clone());
+    let standard_deviation = sample_standard_deviation(values.clone());
+
+    values.map(move |x| (x - mean) / standard_deviation)
+}
+
+pub fn pearson_corr_coeff(points: impl ExactSizeIterator<Item=Point2<f64>> + Clone) -> PearsonCorrCoeff
+{
+    let len = points.len();
+
+    let standard_scores_x = standard_scores(points.clone().map(|p| p.x));
+    let standard_scores_y = standard_scores(points.map(|p| p.y));
+
+    let r = mean_of(
+        standard_scores_x.zip(standard_scores_y).map(|(x, y)| x * y),
+        len - 1
+    );
+
+    let df = (len - 2) as f64;
+
+    let t = (r / math::sqrt(1.0 - math::powi(r, 2))) * math::sqrt(df);
+
+    let p = students_t_pdf(df, t);
+
+    PearsonCorrCoeff{r, p}
+}
+
+// does it with least squares difference
+pub fn best_fit_line(points: impl ExactSizeIterator<Item=Point2<f64>> + Clone) -> Line
+{
+    let mean_x = mean(points.clone().map(|p| p.x));
+    let mean_y = mean(points.clone().map(|p| p.y));
+
+    let top: f64 = points.clone().map(|p|
+    {
+        (p.x - mean_x) * (p.y - mean_y)
+    }).sum();
+
+    let bottom: f64 = points.map(|p|
+    {
+        math::powi(p.x - mean_x, 2)
+    }).sum();
+
+    let slope = top / bottom;
+    let intercept = mean_y - slope * mean_x;
+
+    Line{slope, intercept}
+}
+
+// same as best_fit_line but every point counts as much as the weight next to it,
+// if all the weights are 0 they all count the same instead
+pub fn best_fit_line_weighted(points: impl Iterator<Item=(Point2<f64>, f64)> + Clone) -> Line
+{
+    let uniform = points.clone().map(|(_, weight)| weight).sum::<f64>() == 0.0;
+
+    let points = points.map(move |(p, weight)|
+    {
+        (p, if uniform { 1.0 } else { weight })
+    });
+
+    let total_weight: f64 = points.clone().map(|(_, weight)| weight).sum();
+
+    let mean_x = points.clone().map(|(p, weight)| p.x * weight).sum::<f64>() / total_weight;
+    let mean_y = points.clone().map(|(p, weight)| p.y * weight).sum::<f64>() / total_weight;
+
+    let top: f64 = points.clone().map(|(p, weight)|
+    {
+        weight * (p.x - mean_x) * (p.y - mean_y)
+    }).sum();
+
+    let bottom: f64 = points.map(|(p, weight)|
+    {
+        weight * math::powi(p.x - mean_x, 2)
+    }).sum();
+
+    let slope = top / bottom;
+    let intercept = mean_y - slope * mean_x;
+
+    Line{slope, intercept}
+}
+
+// needs at least 3 points with different x values
+pub fn predict(points: impl ExactSizeIterator<Item=Point2<f64>> + Clone, x: f64) -> Option<Prediction>
+{
+    let len = points.len();
+    if len < 3
+    {
+        return None;
+    }
+
+    let line = best_fit_line(points.clone());
+    if !line.slope.is_finite()
+    {
+        return None;
+    }
+
+    let mean_x = mean(points.clone().map(|p| p.x));
+
+    let squared_errors: f64 = points.clone().map(|p|
+    {
+        math::powi(p.y - line.at_x(p.x), 2)
+    }).sum();
+
+    let spread_x: f64 = points.map(|p| math::powi(p.x - mean_x, 2)).sum();
+
+    let df = (len - 2) as f64;
+    let standard_error = math::sqrt(squared_errors / df);
+
+    let t = students_t_quantile(df, 0.975);
+
+    let half_width = t * standard_error
+        * math::sqrt(1.0 + (len as f64).recip() + math::powi(x - mean_x, 2) / spread_x);
+
+    let mean = line.at_x(x);
+
+    Some(Prediction{mean, lower: mean - half_width, upper: mean + half_width})
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+
+    fn close(a: f64, b: f64) -> bool
+    {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn line_through_exact_points()
+    {
+        let points = [0.0, 1.0, 2.0, 3.0].map(|x| Point2{x, y: 2.0 * x + 1.0});
+
+        let line = best_fit_line(points.into_iter());
+        assert!(close(line.slope, 2.0) && close(line.intercept, 1.0));
+
+        let correlation = pearson_corr_coeff(points.into_iter());
+        assert!(close(correlation.r, 1.0));
+
+        assert!(close(mean(points.into_iter().map(|p| p.y)), 4.0));
+
+        // 1, 3, 5, 7 are 3 and 1 away from the mean, 20 / 3 is the sample variance
+        assert!(close(sample_standard_deviation(points.into_iter().map(|p| p.y)), math::sqrt(20.0 / 3.0)));
+    }
+
+
+    #[test]
+    fn nice_numbers()
+    {
+        assert!(close(nice_number(0.0234, true), 0.02));
+        assert!(close(nice_number(0.0234, false), 0.05));
+
+        assert!(close(nice_number(97.0, true), 100.0));
+        assert!(close(nice_number(97.0, false), 100.0));
+
+        assert!(close(nice_number(3.5, true), 5.0));
+        assert!(close(nice_number(2.0, false), 2.0));
+
+        assert_eq!(nice_number(0.0, true), 0.0);
+        assert_eq!(nice_number(0.0, false), 0.0);
+
+        assert!(close(nice_number(-0.0234, true), -0.02));
+        assert!(close(nice_number(-0.0234, false), -0.05));
+        assert!(close(nice_number(-97.0, false), -100.0));
+    }
+}